@@ -1,9 +1,16 @@
 //! Includes action helper functions for appending actions.
 
 use std::time::Duration;
+use bevy::prelude::*;
 use extension_trait::extension_trait;
-use crate::action::{StartEnv, EndEnv};
+use crate::action::{Action, ActionId, StartEnv, EndEnv};
 use crate::action::common::{End, Quit, Start, Wait};
+use crate::action::composite::Repeat;
+use crate::dialogue::RunDialogue;
+use crate::flags::{Flag, Flags};
+use crate::action::wait::{WaitForAction, WaitForButton, WaitForEvent, WaitUntil};
+use crate::messages::ZoneEntered;
+use crate::action::tween::{Ease, Lens, LightIntensityLens, MaterialAlphaLens, MaterialColorLens, ReflectLens, TransformLens, TranslationLens, Tween, TweenValue};
 
 #[extension_trait]
 pub impl<'a> StartEnvExt for StartEnv<'a> {
@@ -27,6 +34,104 @@ pub impl<'a> StartEnvExt for StartEnv<'a> {
     fn quit(&mut self) {
         self.push(Quit);
     }
+
+    fn wait_until<F>(&mut self, condition: F)
+    where
+        F: FnMut(&mut World) -> bool + Send + Sync + 'static,
+    {
+        self.push(WaitUntil(condition));
+    }
+
+    fn wait_for_event<E: Event>(&mut self) {
+        self.push(WaitForEvent::<E, _>::new(|_event: &E| true));
+    }
+
+    fn wait_for_event_where<E, P>(&mut self, predicate: P)
+    where
+        E: Event,
+        P: Fn(&E) -> bool + Clone + Send + Sync + 'static,
+    {
+        self.push(WaitForEvent::new(predicate));
+    }
+
+    fn wait_for_zone(&mut self, event: &str) {
+        let event = event.to_owned();
+        self.wait_for_event_where(move |entered: &ZoneEntered| entered.event.as_deref() == Some(event.as_str()));
+    }
+
+    fn wait_for_button(&mut self, buttons: u32) {
+        self.push(WaitForButton { entity: None, buttons });
+    }
+
+    fn wait_until_near(&mut self, entity: Entity, position: Vec3, radius: f32) {
+        self.wait_until(move |world: &mut World| {
+            world.get::<Transform>(entity)
+                .is_some_and(|transf| transf.translation.distance(position) <= radius)
+        });
+    }
+
+    fn wait_for_action(&mut self, id: &str) {
+        self.push(WaitForAction(ActionId::new(id)));
+    }
+
+    fn set_flag(&mut self, name: &str, value: impl Into<Flag>) {
+        let (name, value) = (name.to_owned(), value.into());
+        self.start(move |env| env.world.resource_mut::<Flags>().set(name, value));
+    }
+
+    fn add_flag(&mut self, name: &str, amount: i64) {
+        let name = name.to_owned();
+        self.start(move |env| env.world.resource_mut::<Flags>().add(&name, amount));
+    }
+
+    fn wait_for_flag(&mut self, name: &str) {
+        let name = name.to_owned();
+        self.wait_until(move |world: &mut World| world.resource::<Flags>().is_set(&name));
+    }
+
+    fn dialogue(&mut self, path: &str) {
+        self.push(RunDialogue::new(path));
+    }
+
+    fn repeat<A: Action + Clone>(&mut self, times: u32, action: A) {
+        self.push(Repeat::new(times, action));
+    }
+
+    fn tween<L: Lens>(&mut self, entity: Entity, lens: L, seconds: f32, ease: Ease) {
+        self.push(Tween::new(entity, lens, Duration::from_secs_f32(seconds), ease));
+    }
+
+    fn move_to(&mut self, entity: Entity, translation: Vec3, seconds: f32, ease: Ease) {
+        self.tween(entity, TranslationLens::to(translation), seconds, ease);
+    }
+
+    fn transform_to(&mut self, entity: Entity, transform: Transform, seconds: f32, ease: Ease) {
+        self.tween(entity, TransformLens::to(transform), seconds, ease);
+    }
+
+    fn tint_to(&mut self, entity: Entity, color: Color, seconds: f32, ease: Ease) {
+        self.tween(entity, MaterialColorLens::to(color), seconds, ease);
+    }
+
+    fn fade_to(&mut self, entity: Entity, alpha: f32, seconds: f32, ease: Ease) {
+        self.tween(entity, MaterialAlphaLens::to(alpha), seconds, ease);
+    }
+
+    fn light_to(&mut self, entity: Entity, intensity: f32, seconds: f32, ease: Ease) {
+        self.tween(entity, LightIntensityLens::to(intensity), seconds, ease);
+    }
+
+    fn tween_field(
+        &mut self,
+        entity: Entity,
+        component: &str,
+        path: &str,
+        value: impl Into<TweenValue>,
+        seconds: f32,
+        ease: Ease,
+    ) {
+        self.tween(entity, ReflectLens::to(component, path, value), seconds, ease);
+    }
     
     fn start<C, R>(&mut self, callback: C)
     where
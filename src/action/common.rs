@@ -1,7 +1,7 @@
 use std::time::Duration;
-use bevy::prelude::*;
 use crate::act::StartEnvExt;
 use crate::action::{Action, EndEnv, RunEnv, RunStatus, StartEnv};
+use crate::flags::{Flag, Flags};
 
 #[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
 pub struct Wait(pub Duration);
@@ -97,15 +97,10 @@ impl Action for Quit {
     }
 }
 
-#[derive(Resource, Default)]
-struct CutsceneData {
-    times_run: u32,
-}
-
 pub fn cutscene(env: &mut StartEnv) {
-    env.start(|env| env.world.init_resource::<CutsceneData>());
+    env.set_flag("cutscene_loops", Flag::Int(0));
     env.push(cutscene_loop);
-    env.end(|env| env.world.remove_resource::<CutsceneData>());
+    env.add_flag("cutscene_runs", 1);
 }
 
 fn cutscene_loop(env: &mut StartEnv) {
@@ -119,9 +114,8 @@ fn cutscene_loop(env: &mut StartEnv) {
 }
 
 fn loop_if_not_done(env: &mut StartEnv) {
-    let mut data = env.world.resource_mut::<CutsceneData>();
-    data.times_run += 1;
-    if data.times_run < 3 {
+    let times_run = env.world.resource_mut::<Flags>().add("cutscene_loops", 1);
+    if times_run < 3 {
         env.push(cutscene_loop);
     }
     else {
@@ -0,0 +1,171 @@
+//! Actions that own and run other actions.
+
+use std::time::Duration;
+use crate::action::{Action, EndEnv, InnerActionQueue, RunEnv, RunStatus};
+
+
+/// Runs child actions simultaneously.
+/// Finishes when all children have finished.
+/// If any child quits, the remaining children are ended and the parent queue quits.
+#[derive(Default)]
+pub struct Parallel {
+    children: Vec<InnerActionQueue>,
+}
+
+impl Parallel {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a child action.
+    /// Actions pushed in the child's start() run sequentially within that child.
+    pub fn with(mut self, action: impl Action) -> Self {
+        self.children.push(InnerActionQueue::from(action));
+        self
+    }
+}
+
+impl Action for Parallel {
+    fn run(&mut self, env: &mut RunEnv) -> RunStatus {
+        // Every child gets the full delta.
+        // Parallel consumes as much time as its slowest child that finished this frame/tick.
+        let mut time_consumed = Duration::ZERO;
+        let mut child_idx = 0;
+        while child_idx < self.children.len() {
+            let child = &mut self.children[child_idx];
+            match child.update(env.world, env.entity, env.delta) {
+                RunStatus::Running => child_idx += 1,
+                RunStatus::Finished { time_consumed: child_time_consumed } => {
+                    time_consumed = time_consumed.max(child_time_consumed);
+                    self.children.swap_remove(child_idx);
+                },
+                RunStatus::Quit => {
+                    self.children.swap_remove(child_idx);
+                    return RunStatus::Quit;
+                },
+            }
+        }
+        match self.children.is_empty() {
+            true => RunStatus::Finished { time_consumed },
+            false => RunStatus::Running,
+        }
+    }
+
+    fn end(&mut self, env: &mut EndEnv) {
+        for child in &mut self.children {
+            child.end_all(env.world, env.entity);
+        }
+        self.children.clear();
+    }
+}
+
+
+/// Runs child actions simultaneously.
+/// Finishes when the first child finishes, ending the rest.
+/// If any child quits, the remaining children are ended and the parent queue quits.
+#[derive(Default)]
+pub struct Race {
+    children: Vec<InnerActionQueue>,
+}
+
+impl Race {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a child action.
+    /// Actions pushed in the child's start() run sequentially within that child.
+    pub fn with(mut self, action: impl Action) -> Self {
+        self.children.push(InnerActionQueue::from(action));
+        self
+    }
+}
+
+impl Action for Race {
+    fn run(&mut self, env: &mut RunEnv) -> RunStatus {
+        // Every child gets the full delta.
+        // When several children finish on the same frame/tick, the fastest one wins.
+        let mut winner_time_consumed: Option<Duration> = None;
+        let mut child_idx = 0;
+        while child_idx < self.children.len() {
+            let child = &mut self.children[child_idx];
+            match child.update(env.world, env.entity, env.delta) {
+                RunStatus::Running => child_idx += 1,
+                RunStatus::Finished { time_consumed } => {
+                    let fastest = winner_time_consumed.map_or(time_consumed, |winner| winner.min(time_consumed));
+                    winner_time_consumed = Some(fastest);
+                    self.children.swap_remove(child_idx);
+                },
+                RunStatus::Quit => {
+                    self.children.swap_remove(child_idx);
+                    return RunStatus::Quit;
+                },
+            }
+        }
+        match winner_time_consumed {
+            Some(time_consumed) => RunStatus::Finished { time_consumed },
+            None => RunStatus::Running,
+        }
+    }
+
+    fn end(&mut self, env: &mut EndEnv) {
+        for child in &mut self.children {
+            child.end_all(env.world, env.entity);
+        }
+        self.children.clear();
+    }
+}
+
+
+/// Runs a child action a fixed number of times in sequence.
+/// Each repetition runs a fresh clone of the action.
+/// Time left over from a finished repetition carries into the next one.
+pub struct Repeat<A: Action + Clone> {
+    action: A,
+    times: u32,
+    current: Option<InnerActionQueue>,
+}
+
+impl<A: Action + Clone> Repeat<A> {
+    pub fn new(times: u32, action: A) -> Self {
+        Self { action, times, current: None }
+    }
+}
+
+impl<A: Action + Clone> Action for Repeat<A> {
+    fn run(&mut self, env: &mut RunEnv) -> RunStatus {
+        let mut time_remaining = env.delta;
+        loop {
+            if self.current.is_none() {
+                if self.times == 0 {
+                    return RunStatus::Finished { time_consumed: env.delta - time_remaining };
+                }
+                self.times -= 1;
+                self.current = Some(InnerActionQueue::from(self.action.clone()));
+            }
+            let current = self.current.as_mut().unwrap();
+            match current.update(env.world, env.entity, time_remaining) {
+                RunStatus::Running => return RunStatus::Running,
+                RunStatus::Finished { time_consumed } => {
+                    time_remaining = time_remaining.saturating_sub(time_consumed);
+                    self.current = None;
+                    if time_remaining == Duration::ZERO && self.times != 0 {
+                        return RunStatus::Running;
+                    }
+                },
+                RunStatus::Quit => {
+                    self.current = None;
+                    return RunStatus::Quit;
+                },
+            }
+        }
+    }
+
+    fn end(&mut self, env: &mut EndEnv) {
+        if let Some(mut current) = self.current.take() {
+            current.end_all(env.world, env.entity);
+        }
+    }
+}
@@ -1,8 +1,18 @@
 use std::collections::VecDeque;
 use std::time::Duration;
 use bevy::prelude::*;
+use bevy::utils::HashMap;
+use extension_trait::extension_trait;
 use smallvec::SmallVec;
+use crate::dialogue::RunDialogue;
+use crate::script::RunScript;
+use crate::PauseStates;
 pub mod common;
+pub mod composite;
+pub mod tween;
+pub mod wait;
+#[cfg(test)]
+mod tests;
 
 /// A queue of actions that will be executed sequentially over the course of 1 or more frames/ticks.
 /// Useful for stringing together arbitrary actions in a sequence.
@@ -21,14 +31,7 @@ impl Default for ActionQueue {
 
 impl<A: Action> From<A> for ActionQueue {
     fn from(value: A) -> Self {
-        let mut actions: VecDeque<Box<dyn Action>> = VecDeque::new();
-        actions.push_back(Box::new(value));
-        Self::Occupied(InnerActionQueue {
-            running_action: None,
-            remaining_actions: actions,
-            on_finish: OnFinish::default(),
-            quit: false,
-        })
+        Self::Occupied(InnerActionQueue::from(value))
     }
 }
 
@@ -52,6 +55,30 @@ impl ActionQueue {
         self
     }
 
+    /// Sets the rate at which time passes for the queue.
+    /// 1.0 is normal speed, 0.5 is half speed etc.
+    pub fn with_time_scale(mut self, time_scale: f32) -> Self {
+        self.inner_mut().time_scale = time_scale;
+        self
+    }
+
+    /// Sets whether or not the queue pauses when the game is paused.
+    /// Useful for UI animations that should continue while paused.
+    pub fn with_pausable(mut self, pausable: bool) -> Self {
+        self.inner_mut().pausable = pausable;
+        self
+    }
+
+    /// Sets the rate at which time passes for the queue.
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.inner_mut().time_scale = time_scale;
+    }
+
+    /// Pauses or resumes the queue, independently of the game being paused.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.inner_mut().paused = paused;
+    }
+
     /// Schedules the queue to quit.
     pub fn quit(&mut self) {
         match self {
@@ -60,21 +87,78 @@ impl ActionQueue {
         }
     }
 
+    /// Schedules the running action to end early.
+    /// If no action is running, the next action is ended without starting.
+    pub fn skip(&mut self) {
+        self.inner_mut().skip = true;
+    }
+
+    /// Inner queue, if not currently executing.
+    pub fn get(&self) -> Option<&InnerActionQueue> {
+        match self {
+            ActionQueue::Occupied(inner) => Some(inner),
+            ActionQueue::Empty => None,
+        }
+    }
+
     fn inner(self) -> InnerActionQueue {
         match self {
             ActionQueue::Occupied(inner) => inner,
             ActionQueue::Empty => panic!("ActionQueue not occupied"),
         }
     }
+
+    fn inner_mut(&mut self) -> &mut InnerActionQueue {
+        match self {
+            ActionQueue::Occupied(inner) => inner,
+            ActionQueue::Empty => panic!("ActionQueue not occupied"),
+        }
+    }
 }
 
 
-#[derive(Default)]
 pub struct InnerActionQueue {
     running_action: Option<Box<dyn Action>>,
     remaining_actions: VecDeque<Box<dyn Action>>,
     quit: bool,
+    skip: bool,
+    elapsed: Duration,
+    action_elapsed: Duration,
     pub on_finish: OnFinish,
+    /// Rate at which time passes for the queue.
+    pub time_scale: f32,
+    /// If true, queue does not run.
+    pub paused: bool,
+    /// If true, queue does not run while the game is paused.
+    pub pausable: bool,
+}
+
+impl Default for InnerActionQueue {
+    fn default() -> Self {
+        Self {
+            running_action: None,
+            remaining_actions: VecDeque::new(),
+            quit: false,
+            skip: false,
+            elapsed: Duration::ZERO,
+            action_elapsed: Duration::ZERO,
+            on_finish: OnFinish::default(),
+            time_scale: 1.0,
+            paused: false,
+            pausable: true,
+        }
+    }
+}
+
+impl<A: Action> From<A> for InnerActionQueue {
+    fn from(value: A) -> Self {
+        let mut actions: VecDeque<Box<dyn Action>> = VecDeque::new();
+        actions.push_back(Box::new(value));
+        Self {
+            remaining_actions: actions,
+            ..default()
+        }
+    }
 }
 
 impl InnerActionQueue {
@@ -83,16 +167,46 @@ impl InnerActionQueue {
         self.running_action.is_none() && self.remaining_actions.is_empty()
     }
 
-    fn update(&mut self, world: &mut World, entity: Entity, delta: Duration) {
+    /// Action currently running, if any.
+    pub fn running_action(&self) -> Option<&dyn Action> {
+        self.running_action.as_deref()
+    }
+
+    /// Actions that have not started yet, in order.
+    pub fn remaining_actions(&self) -> impl Iterator<Item = &dyn Action> {
+        self.remaining_actions.iter().map(|action| action.as_ref())
+    }
+
+    /// Total time the queue has run for.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Time the running action has run for.
+    pub fn action_elapsed(&self) -> Duration {
+        self.action_elapsed
+    }
+
+    /// Runs actions in the queue for a single frame/tick.
+    /// Returns [`RunStatus::Finished`] if the queue ran out of actions, with the time consumed to do so.
+    /// Returns [`RunStatus::Quit`] if the queue quit.
+    pub(crate) fn update(&mut self, world: &mut World, entity: Entity, delta: Duration) -> RunStatus {
         let mut time_remaining = delta;
+        self.elapsed += delta;
         loop {
 
             if self.quit {
-                while let Some(mut action) = self.remaining_actions.pop_front() {
+                self.end_all(world, entity);
+                self.quit = false;
+                return RunStatus::Quit;
+            }
+
+            if self.skip {
+                let skipped = self.running_action.take().or_else(|| self.remaining_actions.pop_front());
+                if let Some(mut action) = skipped {
                     action.end(&mut EndEnv { world, entity });
                 }
-                self.quit = false;
-                break;
+                self.skip = false;
             }
 
             // Gets next action to run.
@@ -100,13 +214,16 @@ impl InnerActionQueue {
             let mut action = match self.running_action.take() {
                 Some(action) => action,
                 None => {
-                    let Some(mut action) = self.remaining_actions.pop_front() else { break };
+                    let Some(mut action) = self.remaining_actions.pop_front() else {
+                        return RunStatus::Finished { time_consumed: delta - time_remaining };
+                    };
                     action.start(&mut StartEnv {
                         world,
                         entity,
                         actions: &mut self.remaining_actions,
                         insert_idx: 0,
                     });
+                    self.action_elapsed = Duration::ZERO;
                     action
                 },
             };
@@ -116,14 +233,20 @@ impl InnerActionQueue {
                 // Resumes action next tick
                 RunStatus::Running => {
                     self.running_action = Some(action);
-                    break;
+                    self.action_elapsed += time_remaining;
+                    return RunStatus::Running;
                 },
                 // Ends action, and runs the next one if there is one, and there is time remaining.
                 RunStatus::Finished { time_consumed } => {
-                    let time_consumed = time_consumed.max(time_remaining);
+                    let time_consumed = time_consumed.min(time_remaining);
                     time_remaining -= time_consumed;
                     action.end(&mut EndEnv { world, entity });
-                    if time_remaining == Duration::ZERO { break }
+                    if time_remaining == Duration::ZERO {
+                        return match self.is_finished() {
+                            true => RunStatus::Finished { time_consumed: delta },
+                            false => RunStatus::Running,
+                        };
+                    }
                 },
                 // Ends action and every other remaining action.
                 RunStatus::Quit => {
@@ -133,6 +256,16 @@ impl InnerActionQueue {
             }
         }
     }
+
+    /// Ends the running action, and every remaining action.
+    pub(crate) fn end_all(&mut self, world: &mut World, entity: Entity) {
+        if let Some(mut action) = self.running_action.take() {
+            action.end(&mut EndEnv { world, entity });
+        }
+        while let Some(mut action) = self.remaining_actions.pop_front() {
+            action.end(&mut EndEnv { world, entity });
+        }
+    }
 }
 
 
@@ -164,6 +297,15 @@ pub trait Action: Send + Sync + 'static {
     /// Useful for cleanup.
     #[allow(unused)]
     fn end(&mut self, env: &mut EndEnv) {}
+    /// Name displayed when debugging.
+    fn name(&self) -> String {
+        bevy::utils::get_short_name(std::any::type_name::<Self>())
+    }
+    /// Fraction of the action completed, between 0.0 and 1.0, if known.
+    /// Displayed when debugging.
+    fn progress(&self) -> Option<f32> {
+        None
+    }
 }
 
 impl<F, R> Action for F
@@ -211,6 +353,15 @@ pub struct RunEnv<'a> {
     pub delta: Duration,
 }
 
+/// Schedule [`run_action_queues`] runs in.
+/// [`FixedUpdate`](ActionSchedule::FixedUpdate) makes queues advance by a constant delta, which is deterministic.
+#[derive(Resource, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub enum ActionSchedule {
+    #[default]
+    Update,
+    FixedUpdate,
+}
+
 /// Task to perform on the [`Entity`] of an [`ActionQueue`] after all [`Action`]s are exhausted.
 #[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
 pub enum OnFinish {
@@ -221,42 +372,163 @@ pub enum OnFinish {
     DespawnRecursive,
 }
 
-#[derive(Component, Copy, Clone, Eq, PartialEq, Hash, Debug)]
-pub enum ActionKind {
-    Cutscene,
+/// Identifies an action in the [`ActionRegistry`].
+/// Also marks action queues started with [`RunAction`](messages::RunAction).
+#[derive(Component, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ActionId(pub String);
+
+impl ActionId {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
 }
 
-impl ActionKind {
-    pub fn to_action_queue(self) -> ActionQueue {
-        match self {
-            ActionKind::Cutscene => ActionQueue::from(common::cutscene),
+impl From<&str> for ActionId {
+    fn from(id: &str) -> Self {
+        Self(id.to_owned())
+    }
+}
+
+impl std::fmt::Display for ActionId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// What happens when an action is run while an instance of it is already running.
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub enum Exclusivity {
+    /// Any number of instances can run at once.
+    #[default]
+    Multiple,
+    /// Only one instance can run at once. New instances are ignored.
+    Single,
+    /// Only one instance can run at once. Running instances are quit, and replaced with the new one.
+    Restart,
+}
+
+/// Actions that can be run by id with [`RunAction`](messages::RunAction).
+/// Ids ending in ".script.ron" that are not registered are run as [`Script`](crate::script::Script) assets.
+/// Likewise, ids ending in ".dialogue.ron" are shown as [`Dialogue`](crate::dialogue::Dialogue) assets.
+#[derive(Resource, Default)]
+pub struct ActionRegistry {
+    actions: HashMap<ActionId, RegisteredAction>,
+}
+
+struct RegisteredAction {
+    create_queue: Box<dyn Fn() -> ActionQueue + Send + Sync>,
+    exclusivity: Exclusivity,
+}
+
+impl ActionRegistry {
+
+    /// Registers an action under an id, replacing any action previously registered with it.
+    pub fn register<A: Action + Clone>(&mut self, id: impl Into<String>, action: A, exclusivity: Exclusivity) {
+        let create_queue = Box::new(move || ActionQueue::from(action.clone()));
+        self.actions.insert(ActionId::new(id), RegisteredAction { create_queue, exclusivity });
+    }
+
+    /// Registers a [`Script`](crate::script::Script) asset under an id.
+    pub fn register_script(&mut self, id: impl Into<String>, path: impl Into<String>, exclusivity: Exclusivity) {
+        let path = path.into();
+        let create_queue = Box::new(move || ActionQueue::from(RunScript::new(path.clone())));
+        self.actions.insert(ActionId::new(id), RegisteredAction { create_queue, exclusivity });
+    }
+
+    pub fn contains(&self, id: &ActionId) -> bool {
+        self.actions.contains_key(id)
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = &ActionId> {
+        self.actions.keys()
+    }
+
+    /// Creates a new queue for the action specified, along with its exclusivity.
+    fn create(&self, id: &ActionId) -> Option<(ActionQueue, Exclusivity)> {
+        match self.actions.get(id) {
+            Some(action) => Some(((action.create_queue)(), action.exclusivity)),
+            None if id.as_str().ends_with(".script.ron") => {
+                Some((ActionQueue::from(RunScript::new(id.as_str())), Exclusivity::default()))
+            },
+            None if id.as_str().ends_with(".dialogue.ron") => {
+                Some((ActionQueue::from(RunDialogue::new(id.as_str())), Exclusivity::Single))
+            },
+            None => None,
         }
     }
 }
 
+#[extension_trait]
+pub impl AppActionExt for App {
+    /// Registers an action in the [`ActionRegistry`].
+    fn register_action<A: Action + Clone>(&mut self, id: &str, action: A, exclusivity: Exclusivity) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_insert_with(ActionRegistry::default)
+            .register(id, action, exclusivity);
+        self
+    }
+}
+
 pub fn run_action(
     trigger: Trigger<messages::RunAction>,
+    registry: Res<ActionRegistry>,
+    mut action_queues: Query<(Entity, &mut ActionQueue, &ActionId, Has<QuitRequested>)>,
     mut commands: Commands,
 ) {
-    let action_kind = trigger.event().0;
-    let action_queue = action_kind.to_action_queue().with_on_finish(OnFinish::Despawn);
-    commands.spawn((action_queue, action_kind));
+    let action_id = &trigger.event().0;
+    let Some((action_queue, exclusivity)) = registry.create(action_id) else {
+        bevy::log::warn!("Action '{action_id}' not registered");
+        return;
+    };
+    // Instances already quitting do not count as running.
+    let mut running = action_queues
+        .iter_mut()
+        .filter(|(_, queue, id, quit_requested)| {
+            let quitting = *quit_requested || queue.get().is_some_and(|inner| inner.quit);
+            *id == action_id && !quitting
+        });
+    match exclusivity {
+        Exclusivity::Multiple => {},
+        Exclusivity::Single => if running.next().is_some() { return },
+        Exclusivity::Restart => for (entity, mut queue, _, _) in running {
+            quit_queue(entity, &mut queue, &mut commands);
+        },
+    }
+    let action_queue = action_queue.with_on_finish(OnFinish::Despawn);
+    commands.spawn((action_queue, action_id.clone()));
 }
 
 pub fn quit_action(
     trigger: Trigger<messages::QuitAction>,
     mut commands: Commands,
-    mut action_queues: Query<(Entity, &mut ActionQueue, &ActionKind)>,
+    mut action_queues: Query<(Entity, &mut ActionQueue, &ActionId)>,
 ) {
-    let action_kind = trigger.event().0;
-    for (entity, mut queue, kind) in &mut action_queues {
-        if action_kind == *kind {
-            queue.quit();
-            commands.entity(entity).despawn_recursive();
+    let action_id = &trigger.event().0;
+    for (entity, mut queue, id) in &mut action_queues {
+        if action_id == id {
+            quit_queue(entity, &mut queue, &mut commands);
         }
     }
 }
 
+/// Marks a queue that was told to quit while executing.
+/// The quit is applied once the queue is put back on its entity.
+#[derive(Component, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct QuitRequested;
+
+/// Quits a queue, even if it is currently executing. IE: when quit from one of its actions.
+/// The queue is left to its [`OnFinish`], so that its actions end before its entity goes away.
+fn quit_queue(entity: Entity, queue: &mut ActionQueue, commands: &mut Commands) {
+    match queue {
+        ActionQueue::Occupied(inner) => inner.quit = true,
+        ActionQueue::Empty => { commands.entity(entity).insert(QuitRequested); },
+    }
+}
+
 /// Runs all action queues for the current frame/tick.
 pub fn run_action_queues(
     world: &mut World,
@@ -272,13 +544,30 @@ pub fn run_action_queues(
     }
 
     // Runs all action queues for this frame/tick.
+    // Time is the virtual time in Update, and the fixed time in FixedUpdate.
     let delta = world.resource::<Time>().delta();
+    let game_paused = world
+        .get_resource::<State<PauseStates>>()
+        .is_some_and(|state| *state.get() == PauseStates::Paused);
     for (entity, inner_action_queue) in &mut inner_action_queues {
+        // Quits are processed even while paused.
+        let paused = inner_action_queue.paused || (game_paused && inner_action_queue.pausable);
+        if paused && !inner_action_queue.quit { continue };
+        let delta = delta.mul_f32(inner_action_queue.time_scale.max(0.0));
         inner_action_queue.update(world, *entity, delta);
     }
 
     // Decides where to put action queues after executing this frame/tick.
-    for (action_queue_entity, action_queue) in inner_action_queues {
+    // Applies commands of observers triggered while running, such as quit requests.
+    world.flush();
+    for (action_queue_entity, mut action_queue) in inner_action_queues {
+        let quit_requested = world
+            .get_entity_mut(action_queue_entity)
+            .is_some_and(|mut entity| entity.take::<QuitRequested>().is_some());
+        if quit_requested {
+            action_queue.quit = true;
+            action_queue.update(world, action_queue_entity, Duration::ZERO);
+        }
         let (is_finished, on_finish) = (action_queue.is_finished(), action_queue.on_finish);
         match (is_finished, on_finish) {
             (true, OnFinish::Nothing) | (false, _) => {
@@ -303,10 +592,12 @@ pub fn run_action_queues(
 
 pub mod messages {
     use bevy::prelude::*;
-    use super::ActionKind;
+    use super::ActionId;
     
-    #[derive(Event, Copy, Clone, Eq, PartialEq, Hash, Debug)]
-    pub struct RunAction(pub ActionKind);
-    #[derive(Event, Copy, Clone, Eq, PartialEq, Hash, Debug)]
-    pub struct QuitAction(pub ActionKind);
+    /// Runs an action registered in the [`ActionRegistry`](super::ActionRegistry).
+    #[derive(Event, Clone, Eq, PartialEq, Hash, Debug)]
+    pub struct RunAction(pub ActionId);
+    /// Quits all running instances of an action.
+    #[derive(Event, Clone, Eq, PartialEq, Hash, Debug)]
+    pub struct QuitAction(pub ActionId);
 }
\ No newline at end of file
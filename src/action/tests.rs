@@ -0,0 +1,504 @@
+use std::time::Duration;
+use bevy::ecs::observer::ObserverState;
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use crate::action::common::{Quit, Wait};
+use crate::action::composite::{Parallel, Race, Repeat};
+use crate::action::*;
+use crate::action::messages::{QuitAction, RunAction};
+use crate::action::tween::{Ease, TranslationLens, Tween};
+use crate::action::wait::WaitForEvent;
+use crate::PauseStates;
+
+
+/// Names of lifecycle calls, in the order they happened.
+#[derive(Resource, Default)]
+struct Log(Vec<String>);
+
+/// Logs its lifecycle calls, and runs for a number of frames.
+#[derive(Clone)]
+struct Record {
+    name: &'static str,
+    frames: u32,
+}
+
+impl Record {
+    fn new(name: &'static str) -> Self {
+        Self { name, frames: 1 }
+    }
+
+    fn frames(name: &'static str, frames: u32) -> Self {
+        Self { name, frames }
+    }
+}
+
+impl Action for Record {
+    fn start(&mut self, env: &mut StartEnv) {
+        log(env.world, format!("{}.start", self.name));
+    }
+
+    fn run(&mut self, env: &mut RunEnv) -> RunStatus {
+        log(env.world, format!("{}.run", self.name));
+        self.frames -= 1;
+        match self.frames {
+            0 => RunStatus::Finished { time_consumed: Duration::ZERO },
+            _ => RunStatus::Running,
+        }
+    }
+
+    fn end(&mut self, env: &mut EndEnv) {
+        log(env.world, format!("{}.end", self.name));
+    }
+}
+
+fn log(world: &mut World, message: String) {
+    world.resource_mut::<Log>().0.push(message);
+}
+
+fn new_world() -> World {
+    let mut world = World::new();
+    world.init_resource::<Log>();
+    world.init_resource::<Time>();
+    world
+}
+
+/// Advances time, then runs all action queues once.
+fn tick(world: &mut World, millis: u64) {
+    world.resource_mut::<Time>().advance_by(Duration::from_millis(millis));
+    world.run_system_once(run_action_queues);
+}
+
+fn take_log(world: &mut World) -> Vec<String> {
+    std::mem::take(&mut world.resource_mut::<Log>().0)
+}
+
+fn spawn_queue(world: &mut World, queue: ActionQueue) -> Entity {
+    world.spawn(queue.with_on_finish(OnFinish::Nothing)).id()
+}
+
+
+#[test]
+fn start_run_end_order() {
+    let mut world = new_world();
+    let mut queue = ActionQueue::from(Record::frames("a", 2));
+    queue.push(Record::new("b"));
+    spawn_queue(&mut world, queue);
+
+    tick(&mut world, 16);
+    assert_eq!(take_log(&mut world), ["a.start", "a.run"]);
+    tick(&mut world, 16);
+    assert_eq!(take_log(&mut world), ["a.run", "a.end", "b.start", "b.run", "b.end"]);
+    tick(&mut world, 16);
+    assert!(take_log(&mut world).is_empty());
+}
+
+#[test]
+fn waits_chain_within_frame() {
+    let mut world = new_world();
+    let mut queue = ActionQueue::from(Wait(Duration::from_millis(10)));
+    queue.push(Wait(Duration::from_millis(10)));
+    queue.push(Record::new("a"));
+    spawn_queue(&mut world, queue);
+
+    tick(&mut world, 25);
+    assert_eq!(take_log(&mut world), ["a.start", "a.run", "a.end"]);
+}
+
+#[test]
+fn surplus_time_carries_into_next_action() {
+    let mut world = new_world();
+    let mut queue = ActionQueue::from(Wait(Duration::from_millis(10)));
+    queue.push(Wait(Duration::from_millis(20)));
+    queue.push(Record::new("a"));
+    spawn_queue(&mut world, queue);
+
+    tick(&mut world, 15);   // First wait finishes, second wait has 15ms left
+    assert!(take_log(&mut world).is_empty());
+    tick(&mut world, 10);   // Second wait has 5ms left
+    assert!(take_log(&mut world).is_empty());
+    tick(&mut world, 10);   // Second wait finishes
+    assert_eq!(take_log(&mut world), ["a.start", "a.run", "a.end"]);
+}
+
+#[test]
+fn game_pause_stops_pausable_queues() {
+    let mut world = new_world();
+    world.insert_resource(State::new(PauseStates::Paused));
+    spawn_queue(&mut world, ActionQueue::from(Record::new("pausable")));
+    spawn_queue(&mut world, ActionQueue::from(Record::new("unpausable")).with_pausable(false));
+
+    tick(&mut world, 16);
+    assert_eq!(take_log(&mut world), ["unpausable.start", "unpausable.run", "unpausable.end"]);
+    world.insert_resource(State::new(PauseStates::Running));
+    tick(&mut world, 16);
+    assert_eq!(take_log(&mut world), ["pausable.start", "pausable.run", "pausable.end"]);
+}
+
+#[test]
+fn push_inserts_in_order_before_remaining() {
+    let mut world = new_world();
+    let mut queue = ActionQueue::from(|env: &mut StartEnv| {
+        env.push(Record::new("a"));
+        env.push(|env: &mut StartEnv| env.push(Record::new("b1")));
+        env.push(Record::new("c"));
+    });
+    queue.push(Record::new("d"));
+    spawn_queue(&mut world, queue);
+
+    tick(&mut world, 16);
+    assert_eq!(take_log(&mut world), [
+        "a.start", "a.run", "a.end",
+        "b1.start", "b1.run", "b1.end",
+        "c.start", "c.run", "c.end",
+        "d.start", "d.run", "d.end",
+    ]);
+}
+
+#[test]
+fn quit_ends_remaining_actions_without_starting_them() {
+    let mut world = new_world();
+    let mut queue = ActionQueue::from(Record::new("a"));
+    queue.push(Quit);
+    queue.push(Record::new("b"));
+    queue.push(Record::new("c"));
+    let entity = spawn_queue(&mut world, queue);
+
+    tick(&mut world, 16);
+    assert_eq!(take_log(&mut world), ["a.start", "a.run", "a.end", "b.end", "c.end"]);
+    let ActionQueue::Occupied(queue) = world.get::<ActionQueue>(entity).unwrap() else { panic!() };
+    assert!(queue.is_finished());
+}
+
+#[test]
+fn quit_ends_running_action() {
+    let mut world = new_world();
+    let mut queue = ActionQueue::from(Record::frames("a", 10));
+    queue.push(Record::new("b"));
+    let entity = spawn_queue(&mut world, queue);
+
+    tick(&mut world, 16);
+    assert_eq!(take_log(&mut world), ["a.start", "a.run"]);
+    world.get_mut::<ActionQueue>(entity).unwrap().quit();
+    tick(&mut world, 16);
+    assert_eq!(take_log(&mut world), ["a.end", "b.end"]);
+}
+
+#[test]
+fn on_finish_nothing() {
+    let mut world = new_world();
+    let queue = ActionQueue::from(Record::new("a")).with_on_finish(OnFinish::Nothing);
+    let entity = world.spawn(queue).id();
+    tick(&mut world, 16);
+    assert!(world.get::<ActionQueue>(entity).is_some());
+}
+
+#[test]
+fn on_finish_remove_queue() {
+    let mut world = new_world();
+    let queue = ActionQueue::from(Record::new("a")).with_on_finish(OnFinish::RemoveQueue);
+    let entity = world.spawn(queue).id();
+    tick(&mut world, 16);
+    assert!(world.get_entity(entity).is_some());
+    assert!(world.get::<ActionQueue>(entity).is_none());
+}
+
+#[test]
+fn on_finish_despawn() {
+    let mut world = new_world();
+    let queue = ActionQueue::from(Record::new("a")).with_on_finish(OnFinish::Despawn);
+    let child = world.spawn_empty().id();
+    let entity = world.spawn(queue).add_child(child).id();
+    tick(&mut world, 16);
+    assert!(world.get_entity(entity).is_none());
+    assert!(world.get_entity(child).is_some());
+}
+
+#[test]
+fn on_finish_despawn_recursive() {
+    let mut world = new_world();
+    let queue = ActionQueue::from(Record::new("a")).with_on_finish(OnFinish::DespawnRecursive);
+    let child = world.spawn_empty().id();
+    let entity = world.spawn(queue).add_child(child).id();
+    tick(&mut world, 16);
+    assert!(world.get_entity(entity).is_none());
+    assert!(world.get_entity(child).is_none());
+}
+
+#[test]
+fn unfinished_queue_is_kept() {
+    let mut world = new_world();
+    let queue = ActionQueue::from(Record::frames("a", 2)).with_on_finish(OnFinish::Despawn);
+    let entity = world.spawn(queue).id();
+    tick(&mut world, 16);
+    assert!(world.get::<ActionQueue>(entity).is_some());
+    tick(&mut world, 16);
+    assert!(world.get_entity(entity).is_none());
+}
+
+/// World that can run registered actions.
+fn new_registry_world(exclusivity: Exclusivity) -> World {
+    let mut world = new_world();
+    let mut registry = ActionRegistry::default();
+    registry.register("long", Record::frames("long", 10), exclusivity);
+    world.insert_resource(registry);
+    world.observe(run_action);
+    world.observe(quit_action);
+    world
+}
+
+fn run(world: &mut World, id: &str) {
+    world.trigger(RunAction(id.into()));
+    world.flush();
+}
+
+fn quit(world: &mut World, id: &str) {
+    world.trigger(QuitAction(id.into()));
+    world.flush();
+}
+
+fn running_instances(world: &mut World, id: &str) -> usize {
+    let id = ActionId::from(id);
+    world.query::<&ActionId>().iter(world).filter(|running| **running == id).count()
+}
+
+#[test]
+fn run_action_multiple() {
+    let mut world = new_registry_world(Exclusivity::Multiple);
+    run(&mut world, "long");
+    run(&mut world, "long");
+    assert_eq!(running_instances(&mut world, "long"), 2);
+}
+
+#[test]
+fn run_action_single_ignores_new_instances() {
+    let mut world = new_registry_world(Exclusivity::Single);
+    run(&mut world, "long");
+    let first = world.query_filtered::<Entity, With<ActionId>>().single(&world);
+    run(&mut world, "long");
+    assert_eq!(running_instances(&mut world, "long"), 1);
+    assert!(world.get_entity(first).is_some());
+}
+
+#[test]
+fn run_action_restart_replaces_running_instance() {
+    let mut world = new_registry_world(Exclusivity::Restart);
+    run(&mut world, "long");
+    let first = world.query_filtered::<Entity, With<ActionId>>().single(&world);
+    run(&mut world, "long");
+    tick(&mut world, 16);
+    assert_eq!(running_instances(&mut world, "long"), 1);
+    assert!(world.get_entity(first).is_none());
+    assert!(take_log(&mut world).contains(&"long.end".to_owned()));
+}
+
+#[test]
+fn run_action_unregistered_is_ignored() {
+    let mut world = new_registry_world(Exclusivity::Multiple);
+    run(&mut world, "missing");
+    assert_eq!(running_instances(&mut world, "missing"), 0);
+}
+
+#[test]
+fn quit_action_despawns_all_instances() {
+    let mut world = new_registry_world(Exclusivity::Multiple);
+    run(&mut world, "long");
+    run(&mut world, "long");
+    quit(&mut world, "long");
+    tick(&mut world, 16);
+    assert_eq!(running_instances(&mut world, "long"), 0);
+    let log = take_log(&mut world);
+    assert_eq!(2, log.iter().filter(|message| *message == "long.end").count());
+}
+
+/// Triggers an event when run.
+struct Fire<E: Event + Clone>(E);
+
+impl<E: Event + Clone> Action for Fire<E> {
+    fn run(&mut self, env: &mut RunEnv) -> RunStatus {
+        env.world.trigger(self.0.clone());
+        RunStatus::Finished { time_consumed: Duration::ZERO }
+    }
+}
+
+#[test]
+fn run_action_restart_from_running_action() {
+    let mut world = new_registry_world(Exclusivity::Restart);
+    run(&mut world, "long");
+    let first = world.query_filtered::<Entity, With<ActionId>>().single(&world);
+    tick(&mut world, 16);
+    take_log(&mut world);
+
+    spawn_queue(&mut world, ActionQueue::from(Fire(RunAction("long".into()))));
+    tick(&mut world, 16);
+    assert_eq!(running_instances(&mut world, "long"), 1);
+    assert!(world.get_entity(first).is_none());
+    assert!(take_log(&mut world).contains(&"long.end".to_owned()));
+}
+
+#[test]
+fn quit_action_from_running_action() {
+    let mut world = new_registry_world(Exclusivity::Multiple);
+    run(&mut world, "long");
+    tick(&mut world, 16);
+    take_log(&mut world);
+
+    spawn_queue(&mut world, ActionQueue::from(Fire(QuitAction("long".into()))));
+    tick(&mut world, 16);
+    assert_eq!(running_instances(&mut world, "long"), 0);
+    assert!(take_log(&mut world).contains(&"long.end".to_owned()));
+}
+
+#[test]
+fn quit_action_ends_event_waits() {
+    let mut world = new_registry_world(Exclusivity::Multiple);
+    let queue = ActionQueue::from(WaitForEvent::new(|_: &Ping| true)).with_on_finish(OnFinish::Despawn);
+    world.spawn((queue, ActionId::from("wait")));
+    let observers = observer_count(&mut world);
+    tick(&mut world, 16);
+    assert_eq!(observers + 1, observer_count(&mut world));
+
+    quit(&mut world, "wait");
+    tick(&mut world, 16);
+    assert_eq!(running_instances(&mut world, "wait"), 0);
+    assert_eq!(observers, observer_count(&mut world));
+}
+
+
+#[test]
+fn parallel_finishes_when_all_children_finish() {
+    let parallel = Parallel::new()
+        .with(Record::new("a"))
+        .with(Record::frames("b", 2));
+    let mut queue = ActionQueue::from(parallel);
+    queue.push(Record::new("c"));
+    let mut world = new_world();
+    spawn_queue(&mut world, queue);
+
+    tick(&mut world, 10);
+    assert_eq!(take_log(&mut world), ["a.start", "a.run", "a.end", "b.start", "b.run"]);
+    tick(&mut world, 10);
+    assert_eq!(take_log(&mut world), ["b.run", "b.end", "c.start", "c.run", "c.end"]);
+}
+
+#[test]
+fn race_ends_losers() {
+    let race = Race::new()
+        .with(Record::frames("a", 3))
+        .with(Record::new("b"));
+    let mut queue = ActionQueue::from(race);
+    queue.push(Record::new("c"));
+    let mut world = new_world();
+    spawn_queue(&mut world, queue);
+
+    tick(&mut world, 10);
+    assert_eq!(take_log(&mut world), ["a.start", "a.run", "b.start", "b.run", "b.end", "a.end", "c.start", "c.run", "c.end"]);
+    tick(&mut world, 10);
+    assert!(take_log(&mut world).is_empty());
+}
+
+#[test]
+fn repeat_carries_surplus_time_between_iterations() {
+    let mut queue = ActionQueue::from(Repeat::new(3, Wait(Duration::from_millis(10))));
+    queue.push(Record::new("a"));
+    let mut world = new_world();
+    spawn_queue(&mut world, queue);
+
+    tick(&mut world, 25);   // Two waits finish, third has 5ms left
+    assert!(take_log(&mut world).is_empty());
+    tick(&mut world, 10);   // Third wait finishes, with time left over for the next action
+    assert_eq!(take_log(&mut world), ["a.start", "a.run", "a.end"]);
+}
+
+
+fn running_progress(world: &World, queue: Entity) -> Option<f32> {
+    world.get::<ActionQueue>(queue)?.get()?.running_action()?.progress()
+}
+
+#[test]
+fn tween_interpolates_over_duration() {
+    let mut world = new_world();
+    let target = world.spawn(Transform::default()).id();
+    let lens = TranslationLens::to(Vec3::new(10.0, 0.0, 0.0));
+    let mut queue = ActionQueue::from(Tween::new(target, lens, Duration::from_millis(100), Ease::Linear));
+    queue.push(Record::new("a"));
+    let queue = spawn_queue(&mut world, queue);
+
+    tick(&mut world, 25);
+    assert_eq!(Some(0.25), running_progress(&world, queue));
+    assert_eq!(2.5, world.get::<Transform>(target).unwrap().translation.x);
+    tick(&mut world, 50);
+    assert_eq!(Some(0.75), running_progress(&world, queue));
+    assert_eq!(7.5, world.get::<Transform>(target).unwrap().translation.x);
+    assert!(take_log(&mut world).is_empty());
+    tick(&mut world, 50);   // Finishes with 25ms left over for the next action
+    assert_eq!(10.0, world.get::<Transform>(target).unwrap().translation.x);
+    assert_eq!(take_log(&mut world), ["a.start", "a.run", "a.end"]);
+}
+
+#[test]
+fn tween_starts_from_current_value() {
+    let mut world = new_world();
+    let target = world.spawn(Transform::from_xyz(10.0, 0.0, 0.0)).id();
+    let lens = TranslationLens::to(Vec3::new(20.0, 0.0, 0.0));
+    spawn_queue(&mut world, ActionQueue::from(Tween::new(target, lens, Duration::from_millis(100), Ease::Linear)));
+
+    tick(&mut world, 50);
+    assert_eq!(15.0, world.get::<Transform>(target).unwrap().translation.x);
+}
+
+#[test]
+fn ease_endpoints() {
+    let eases = [
+        Ease::Linear,
+        Ease::QuadIn, Ease::QuadOut, Ease::QuadInOut,
+        Ease::CubicIn, Ease::CubicOut, Ease::CubicInOut,
+        Ease::SineIn, Ease::SineOut, Ease::SineInOut,
+        Ease::BackOut,
+    ];
+    for ease in eases {
+        assert!(ease.sample(0.0).abs() < 1e-5, "{ease:?} does not start at 0");
+        assert!((ease.sample(1.0) - 1.0).abs() < 1e-5, "{ease:?} does not end at 1");
+        assert_eq!(ease.sample(0.0), ease.sample(-1.0), "{ease:?} not clamped below 0");
+        assert_eq!(ease.sample(1.0), ease.sample(2.0), "{ease:?} not clamped above 1");
+    }
+}
+
+
+#[derive(Event, Copy, Clone, Eq, PartialEq, Debug)]
+struct Ping(u32);
+
+fn observer_count(world: &mut World) -> usize {
+    world.query::<&ObserverState>().iter(world).count()
+}
+
+#[test]
+fn wait_for_event_finishes_on_matching_event() {
+    let mut world = new_world();
+    let mut queue = ActionQueue::from(WaitForEvent::new(|ping: &Ping| ping.0 == 2));
+    queue.push(Record::new("a"));
+    spawn_queue(&mut world, queue);
+
+    tick(&mut world, 10);
+    assert_eq!(1, observer_count(&mut world));
+    world.trigger(Ping(1));
+    world.flush();
+    tick(&mut world, 10);
+    assert!(take_log(&mut world).is_empty());
+    world.trigger(Ping(2));
+    world.flush();
+    tick(&mut world, 10);
+    assert_eq!(take_log(&mut world), ["a.start", "a.run", "a.end"]);
+    assert_eq!(0, observer_count(&mut world));
+}
+
+#[test]
+fn wait_for_event_despawns_observer_on_end() {
+    let mut world = new_world();
+    let queue = spawn_queue(&mut world, ActionQueue::from(WaitForEvent::new(|_: &Ping| true)));
+
+    tick(&mut world, 10);
+    assert_eq!(1, observer_count(&mut world));
+    world.get_mut::<ActionQueue>(queue).unwrap().quit();
+    tick(&mut world, 10);
+    assert_eq!(0, observer_count(&mut world));
+}
@@ -0,0 +1,379 @@
+//! Actions that interpolate values over time.
+
+use std::f32::consts::PI;
+use std::time::Duration;
+use bevy::prelude::*;
+use bevy::reflect::GetPath;
+use serde::Deserialize;
+use crate::action::{Action, RunEnv, RunStatus, StartEnv};
+
+
+/// Interpolates a value on an [`Entity`] over a fixed duration.
+/// The starting value is captured when the action starts, so tweens can be chained.
+pub struct Tween<L: Lens> {
+    pub entity: Entity,
+    pub lens: L,
+    pub duration: Duration,
+    pub ease: Ease,
+    elapsed: Duration,
+}
+
+impl<L: Lens> Tween<L> {
+    pub fn new(entity: Entity, lens: L, duration: Duration, ease: Ease) -> Self {
+        Self { entity, lens, duration, ease, elapsed: Duration::ZERO }
+    }
+}
+
+impl<L: Lens> Action for Tween<L> {
+
+    fn start(&mut self, env: &mut StartEnv) {
+        self.lens.start(env.world, self.entity);
+    }
+
+    fn run(&mut self, env: &mut RunEnv) -> RunStatus {
+        self.elapsed += env.delta;
+        let t = match self.duration.is_zero() {
+            true => 1.0,
+            false => (self.elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.0),
+        };
+        self.lens.apply(env.world, self.entity, self.ease.sample(t));
+        if self.elapsed < self.duration {
+            return RunStatus::Running;
+        }
+        let overshoot = self.elapsed - self.duration;
+        RunStatus::Finished { time_consumed: env.delta - overshoot }
+    }
+
+    fn progress(&self) -> Option<f32> {
+        match self.duration.is_zero() {
+            true => Some(1.0),
+            false => Some((self.elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.0)),
+        }
+    }
+}
+
+
+/// Easing curve of a [`Tween`].
+#[derive(Deserialize, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub enum Ease {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    /// Overshoots the end value slightly before settling.
+    BackOut,
+}
+
+impl Ease {
+
+    /// Maps linear progress t (0.0 - 1.0) to eased progress.
+    pub fn sample(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Ease::Linear        => t,
+            Ease::QuadIn        => t * t,
+            Ease::QuadOut       => 1.0 - (1.0 - t) * (1.0 - t),
+            Ease::QuadInOut     => match t < 0.5 {
+                true => 2.0 * t * t,
+                false => 1.0 - (-2.0 * t + 2.0).powi(2) / 2.0,
+            },
+            Ease::CubicIn       => t * t * t,
+            Ease::CubicOut      => 1.0 - (1.0 - t).powi(3),
+            Ease::CubicInOut    => match t < 0.5 {
+                true => 4.0 * t * t * t,
+                false => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+            },
+            Ease::SineIn        => 1.0 - (t * PI / 2.0).cos(),
+            Ease::SineOut       => (t * PI / 2.0).sin(),
+            Ease::SineInOut     => -((PI * t).cos() - 1.0) / 2.0,
+            Ease::BackOut       => {
+                const C1: f32 = 1.70158;
+                const C3: f32 = C1 + 1.0;
+                1.0 + C3 * (t - 1.0).powi(3) + C1 * (t - 1.0).powi(2)
+            },
+        }
+    }
+}
+
+
+/// Reads and writes the value a [`Tween`] animates.
+pub trait Lens: Send + Sync + 'static {
+    /// Captures the starting value.
+    fn start(&mut self, world: &mut World, entity: Entity);
+    /// Writes the value at eased progress t.
+    fn apply(&mut self, world: &mut World, entity: Entity, t: f32);
+}
+
+/// Animates the translation of a [`Transform`].
+#[derive(Copy, Clone, PartialEq, Default, Debug)]
+pub struct TranslationLens {
+    pub start: Vec3,
+    pub end: Vec3,
+}
+
+impl TranslationLens {
+    pub fn to(end: Vec3) -> Self {
+        Self { start: end, end }
+    }
+}
+
+impl Lens for TranslationLens {
+    fn start(&mut self, world: &mut World, entity: Entity) {
+        let Some(transf) = world.get::<Transform>(entity) else { return };
+        self.start = transf.translation;
+    }
+
+    fn apply(&mut self, world: &mut World, entity: Entity, t: f32) {
+        let Some(mut transf) = world.get_mut::<Transform>(entity) else { return };
+        transf.translation = self.start.lerp(self.end, t);
+    }
+}
+
+/// Animates an entire [`Transform`].
+#[derive(Copy, Clone, PartialEq, Default, Debug)]
+pub struct TransformLens {
+    pub start: Transform,
+    pub end: Transform,
+}
+
+impl TransformLens {
+    pub fn to(end: Transform) -> Self {
+        Self { start: end, end }
+    }
+}
+
+impl Lens for TransformLens {
+    fn start(&mut self, world: &mut World, entity: Entity) {
+        let Some(transf) = world.get::<Transform>(entity) else { return };
+        self.start = *transf;
+    }
+
+    fn apply(&mut self, world: &mut World, entity: Entity, t: f32) {
+        let Some(mut transf) = world.get_mut::<Transform>(entity) else { return };
+        transf.translation = self.start.translation.lerp(self.end.translation, t);
+        transf.rotation = self.start.rotation.slerp(self.end.rotation, t);
+        transf.scale = self.start.scale.lerp(self.end.scale, t);
+    }
+}
+
+/// Animates the base color of an entity's [`StandardMaterial`].
+/// Note that other entities sharing the material are affected too.
+#[derive(Copy, Clone, PartialEq, Default, Debug)]
+pub struct MaterialColorLens {
+    pub start: Color,
+    pub end: Color,
+}
+
+impl MaterialColorLens {
+    pub fn to(end: Color) -> Self {
+        Self { start: end, end }
+    }
+}
+
+impl Lens for MaterialColorLens {
+    fn start(&mut self, world: &mut World, entity: Entity) {
+        let Some(handle) = world.get::<Handle<StandardMaterial>>(entity) else { return };
+        let Some(material) = world.resource::<Assets<StandardMaterial>>().get(handle) else { return };
+        self.start = material.base_color;
+    }
+
+    fn apply(&mut self, world: &mut World, entity: Entity, t: f32) {
+        let Some(handle) = world.get::<Handle<StandardMaterial>>(entity).cloned() else { return };
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let Some(material) = materials.get_mut(&handle) else { return };
+        material.base_color = self.start.mix(&self.end, t);
+    }
+}
+
+/// Animates the alpha of an entity's [`StandardMaterial`].
+/// Note that other entities sharing the material are affected too.
+#[derive(Copy, Clone, PartialEq, Default, Debug)]
+pub struct MaterialAlphaLens {
+    pub start: f32,
+    pub end: f32,
+}
+
+impl MaterialAlphaLens {
+    pub fn to(end: f32) -> Self {
+        Self { start: end, end }
+    }
+}
+
+impl Lens for MaterialAlphaLens {
+    fn start(&mut self, world: &mut World, entity: Entity) {
+        let Some(handle) = world.get::<Handle<StandardMaterial>>(entity) else { return };
+        let Some(material) = world.resource::<Assets<StandardMaterial>>().get(handle) else { return };
+        self.start = material.base_color.alpha();
+    }
+
+    fn apply(&mut self, world: &mut World, entity: Entity, t: f32) {
+        let Some(handle) = world.get::<Handle<StandardMaterial>>(entity).cloned() else { return };
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let Some(material) = materials.get_mut(&handle) else { return };
+        material.base_color.set_alpha(self.start.lerp(self.end, t));
+    }
+}
+
+/// Animates the intensity of a [`PointLight`] or [`SpotLight`], or the illuminance of a [`DirectionalLight`].
+#[derive(Copy, Clone, PartialEq, Default, Debug)]
+pub struct LightIntensityLens {
+    pub start: f32,
+    pub end: f32,
+}
+
+impl LightIntensityLens {
+    pub fn to(end: f32) -> Self {
+        Self { start: end, end }
+    }
+}
+
+impl Lens for LightIntensityLens {
+    fn start(&mut self, world: &mut World, entity: Entity) {
+        if let Some(light) = world.get::<PointLight>(entity) {
+            self.start = light.intensity;
+        }
+        else if let Some(light) = world.get::<SpotLight>(entity) {
+            self.start = light.intensity;
+        }
+        else if let Some(light) = world.get::<DirectionalLight>(entity) {
+            self.start = light.illuminance;
+        }
+    }
+
+    fn apply(&mut self, world: &mut World, entity: Entity, t: f32) {
+        let value = self.start.lerp(self.end, t);
+        if let Some(mut light) = world.get_mut::<PointLight>(entity) {
+            light.intensity = value;
+        }
+        else if let Some(mut light) = world.get_mut::<SpotLight>(entity) {
+            light.intensity = value;
+        }
+        else if let Some(mut light) = world.get_mut::<DirectionalLight>(entity) {
+            light.illuminance = value;
+        }
+    }
+}
+
+/// Animates a field of any reflected [`Component`] by path.
+/// IE: component "PointLight", path "range".
+/// Component must be registered with `#[reflect(Component)]`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ReflectLens {
+    /// Short or full type path of the component.
+    pub component: String,
+    /// Path to the field within the component.
+    pub path: String,
+    pub start: TweenValue,
+    pub end: TweenValue,
+}
+
+impl ReflectLens {
+    pub fn to(component: impl Into<String>, path: impl Into<String>, end: impl Into<TweenValue>) -> Self {
+        let end = end.into();
+        Self { component: component.into(), path: path.into(), start: end, end }
+    }
+
+    fn with_field(&self, world: &mut World, entity: Entity, callback: impl FnOnce(&mut dyn Reflect)) {
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let registry = registry.read();
+        let registration = registry
+            .get_with_short_type_path(&self.component)
+            .or_else(|| registry.get_with_type_path(&self.component));
+        let Some(registration) = registration else {
+            bevy::log::warn!("Component '{}' not registered", self.component);
+            return;
+        };
+        let Some(reflect_component) = registration.data::<ReflectComponent>() else {
+            bevy::log::warn!("Type '{}' does not reflect Component", self.component);
+            return;
+        };
+        let Some(entity) = world.get_entity_mut(entity) else { return };
+        let Some(mut component) = reflect_component.reflect_mut(entity) else { return };
+        match component.reflect_path_mut(self.path.as_str()) {
+            Ok(field) => callback(field),
+            Err(err) => bevy::log::warn!("Invalid path '{}' on '{}': {err}", self.path, self.component),
+        }
+    }
+}
+
+impl Lens for ReflectLens {
+    fn start(&mut self, world: &mut World, entity: Entity) {
+        let mut start = None;
+        self.with_field(world, entity, |field| start = TweenValue::read(field));
+        match start {
+            Some(start) => self.start = start,
+            None => bevy::log::warn!("Field '{}' of '{}' cannot be tweened", self.path, self.component),
+        }
+    }
+
+    fn apply(&mut self, world: &mut World, entity: Entity, t: f32) {
+        let Some(value) = self.start.lerp(&self.end, t) else { return };
+        self.with_field(world, entity, |field| value.write(field));
+    }
+}
+
+/// A value that a [`ReflectLens`] can interpolate.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum TweenValue {
+    F32(f32),
+    Vec2(Vec2),
+    Vec3(Vec3),
+    Vec4(Vec4),
+    Quat(Quat),
+    Color(Color),
+}
+
+impl TweenValue {
+
+    fn read(field: &dyn Reflect) -> Option<Self> {
+        let any = field.as_any();
+        if let Some(value) = any.downcast_ref::<f32>()        { return Some(Self::F32(*value)) };
+        if let Some(value) = any.downcast_ref::<Vec2>()       { return Some(Self::Vec2(*value)) };
+        if let Some(value) = any.downcast_ref::<Vec3>()       { return Some(Self::Vec3(*value)) };
+        if let Some(value) = any.downcast_ref::<Vec4>()       { return Some(Self::Vec4(*value)) };
+        if let Some(value) = any.downcast_ref::<Quat>()       { return Some(Self::Quat(*value)) };
+        if let Some(value) = any.downcast_ref::<Color>()      { return Some(Self::Color(*value)) };
+        None
+    }
+
+    fn write(self, field: &mut dyn Reflect) {
+        let any = field.as_any_mut();
+        match self {
+            Self::F32(value)    => if let Some(field) = any.downcast_mut::<f32>()   { *field = value },
+            Self::Vec2(value)   => if let Some(field) = any.downcast_mut::<Vec2>()  { *field = value },
+            Self::Vec3(value)   => if let Some(field) = any.downcast_mut::<Vec3>()  { *field = value },
+            Self::Vec4(value)   => if let Some(field) = any.downcast_mut::<Vec4>()  { *field = value },
+            Self::Quat(value)   => if let Some(field) = any.downcast_mut::<Quat>()  { *field = value },
+            Self::Color(value)  => if let Some(field) = any.downcast_mut::<Color>() { *field = value },
+        }
+    }
+
+    /// Interpolates between two values of the same kind.
+    /// [`None`] if kinds differ.
+    fn lerp(&self, end: &Self, t: f32) -> Option<Self> {
+        let result = match (self, end) {
+            (Self::F32(a), Self::F32(b))        => Self::F32(a.lerp(*b, t)),
+            (Self::Vec2(a), Self::Vec2(b))      => Self::Vec2(a.lerp(*b, t)),
+            (Self::Vec3(a), Self::Vec3(b))      => Self::Vec3(a.lerp(*b, t)),
+            (Self::Vec4(a), Self::Vec4(b))      => Self::Vec4(a.lerp(*b, t)),
+            (Self::Quat(a), Self::Quat(b))      => Self::Quat(a.slerp(*b, t)),
+            (Self::Color(a), Self::Color(b))    => Self::Color(a.mix(b, t)),
+            _ => return None,
+        };
+        Some(result)
+    }
+}
+
+impl From<f32> for TweenValue   { fn from(value: f32) -> Self { Self::F32(value) } }
+impl From<Vec2> for TweenValue  { fn from(value: Vec2) -> Self { Self::Vec2(value) } }
+impl From<Vec3> for TweenValue  { fn from(value: Vec3) -> Self { Self::Vec3(value) } }
+impl From<Vec4> for TweenValue  { fn from(value: Vec4) -> Self { Self::Vec4(value) } }
+impl From<Quat> for TweenValue  { fn from(value: Quat) -> Self { Self::Quat(value) } }
+impl From<Color> for TweenValue { fn from(value: Color) -> Self { Self::Color(value) } }
@@ -0,0 +1,133 @@
+//! Actions that wait on conditions, events and other action queues.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use bevy::prelude::*;
+use crate::action::{Action, ActionId, EndEnv, RunEnv, RunStatus, StartEnv};
+use crate::input::VButtons;
+
+
+/// Waits until a condition, polled once per frame/tick, is true.
+/// Consumes no time on the frame/tick it finishes.
+pub struct WaitUntil<F>(pub F)
+where
+    F: FnMut(&mut World) -> bool + Send + Sync + 'static;
+
+impl<F> Action for WaitUntil<F>
+where
+    F: FnMut(&mut World) -> bool + Send + Sync + 'static,
+{
+    fn run(&mut self, env: &mut RunEnv) -> RunStatus {
+        match (self.0)(env.world) {
+            true => RunStatus::Finished { time_consumed: Duration::ZERO },
+            false => RunStatus::Running,
+        }
+    }
+}
+
+
+/// Waits until an [`Event`] matching a predicate is triggered.
+/// Only events triggered after this action starts are observed.
+/// Targeted triggers are observed as well.
+pub struct WaitForEvent<E, P>
+where
+    E: Event,
+    P: Fn(&E) -> bool + Clone + Send + Sync + 'static,
+{
+    predicate: P,
+    received: Arc<AtomicBool>,
+    observer: Option<Entity>,
+    _event: std::marker::PhantomData<fn(E)>,
+}
+
+impl<E, P> WaitForEvent<E, P>
+where
+    E: Event,
+    P: Fn(&E) -> bool + Clone + Send + Sync + 'static,
+{
+    pub fn new(predicate: P) -> Self {
+        Self {
+            predicate,
+            received: Arc::new(AtomicBool::new(false)),
+            observer: None,
+            _event: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<E, P> Action for WaitForEvent<E, P>
+where
+    E: Event,
+    P: Fn(&E) -> bool + Clone + Send + Sync + 'static,
+{
+    fn start(&mut self, env: &mut StartEnv) {
+        let received = self.received.clone();
+        let predicate = self.predicate.clone();
+        let observer = Observer::new(move |trigger: Trigger<E>| {
+            if predicate(trigger.event()) {
+                received.store(true, Ordering::Relaxed);
+            }
+        });
+        self.observer = Some(env.world.spawn(observer).id());
+    }
+
+    fn run(&mut self, _env: &mut RunEnv) -> RunStatus {
+        match self.received.load(Ordering::Relaxed) {
+            true => RunStatus::Finished { time_consumed: Duration::ZERO },
+            false => RunStatus::Running,
+        }
+    }
+
+    fn end(&mut self, env: &mut EndEnv) {
+        let Some(observer) = self.observer.take() else { return };
+        if let Some(observer) = env.world.get_entity_mut(observer) {
+            observer.despawn();
+        }
+    }
+}
+
+
+/// Waits until any of the buttons specified are just pressed.
+/// If no entity is specified, any entity with [`VButtons`] can press them.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct WaitForButton {
+    pub entity: Option<Entity>,
+    pub buttons: u32,
+}
+
+impl Action for WaitForButton {
+    fn run(&mut self, env: &mut RunEnv) -> RunStatus {
+        let pressed = match self.entity {
+            Some(entity) => env.world
+                .get::<VButtons>(entity)
+                .is_some_and(|vbuttons| vbuttons.just_pressed(self.buttons)),
+            None => env.world
+                .query::<&VButtons>()
+                .iter(env.world)
+                .any(|vbuttons| vbuttons.just_pressed(self.buttons)),
+        };
+        match pressed {
+            true => RunStatus::Finished { time_consumed: Duration::ZERO },
+            false => RunStatus::Running,
+        }
+    }
+}
+
+
+/// Waits until no instances of the action specified are running.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct WaitForAction(pub ActionId);
+
+impl Action for WaitForAction {
+    fn run(&mut self, env: &mut RunEnv) -> RunStatus {
+        let running = env.world
+            .query::<&ActionId>()
+            .iter(env.world)
+            .any(|id| *id == self.0);
+        match running {
+            true => RunStatus::Running,
+            false => RunStatus::Finished { time_consumed: Duration::ZERO },
+        }
+    }
+}
@@ -132,7 +132,7 @@ impl Default for AnimationState {
 /// Current behavior of an [`AnimationState`].
 #[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
 pub enum AnimationMode {
-    #[allow(unused)]
+    /// Plays once, stopping on the last frame.
     Play,
     #[default]
     Loop,
@@ -171,7 +171,7 @@ pub fn update_animations(
             // Updates frame index if the frame completed
             if anim_state.frame_elapsed > frame.duration {
                 anim_state.frame_idx = match anim_state.mode {
-                    AnimationMode::Play => (anim_state.frame_idx + 1).max(anim.frames.len() - 1),
+                    AnimationMode::Play => (anim_state.frame_idx + 1).min(anim.frames.len() - 1),
                     AnimationMode::Loop => (anim_state.frame_idx + 1) % anim.frames.len(),
                 };
                 anim_state.frame_elapsed -= frame.duration;
@@ -103,41 +103,41 @@ pub fn stream_current_area(
     let Some(mut current_area) = current_area else { return };
     let Some(area) = areas.get_mut(&current_area.area) else { return };
     for map_ref in &area.maps {
-        for (streamer, transf) in &streamers {
 
-            // Computes streamer bounds
+        // Computes map bounds
+        let map_left = map_ref.x as f32;
+        let map_right = map_left + map_ref.width as f32;
+        let map_bottom = -map_ref.y as f32;
+        let map_top = map_bottom + map_ref.height as f32;
+        let map_path = format!("worlds/{}", map_ref.file_name);
+
+        // Checks if any streamer is touching the map
+        let streamer_touching_map = streamers.iter().any(|(streamer, transf)| {
             let stream_pos = Vec2::new(transf.translation.x, transf.translation.y - transf.translation.z);
             let stream_hsize = streamer.size / 2.0;
             let stream_left = stream_pos.x - stream_hsize.x;
             let stream_right = stream_pos.x + stream_hsize.x;
             let stream_top = stream_pos.y + stream_hsize.y;
             let stream_bottom = stream_pos.y - stream_hsize.y;
-            
-            // Computes map bounds
-            let map_left = map_ref.x as f32;
-            let map_right = map_left + map_ref.width as f32;
-            let map_bottom = -map_ref.y as f32;
-            let map_top = map_bottom + map_ref.height as f32;
-            let map_path = format!("worlds/{}", map_ref.file_name);
-
-            // Loads / unloads maps
-            let streamer_touching_map = stream_left <= map_right && stream_right >= map_left && stream_bottom <= map_top && stream_top >= map_bottom;
-            let map_is_loaded = current_area.loaded_maps.contains_key(&map_path);
-            match (streamer_touching_map, map_is_loaded) {
-                (true, false) => {
-                    let map_rect = Rect::new(map_left, map_top, map_right, map_bottom);                    
-                    current_area.loaded_maps.insert(map_path.clone(), map_rect);
-                    commands.trigger(SpawnMap {
-                        file: map_path,
-                        position: Vec3::new(map_ref.x as f32, 0.0, map_ref.y as f32)
-                    });
-                },
-                (false, true) => {
-                    current_area.loaded_maps.remove(&map_path);
-                    commands.trigger(DespawnMap { file: map_path });
-                },
-                _ => {}
-            }
+            stream_left <= map_right && stream_right >= map_left && stream_bottom <= map_top && stream_top >= map_bottom
+        });
+
+        // Loads / unloads maps
+        let map_is_loaded = current_area.loaded_maps.contains_key(&map_path);
+        match (streamer_touching_map, map_is_loaded) {
+            (true, false) => {
+                let map_rect = Rect::new(map_left, map_top, map_right, map_bottom);
+                current_area.loaded_maps.insert(map_path.clone(), map_rect);
+                commands.trigger(SpawnMap {
+                    file: map_path,
+                    position: Vec3::new(map_ref.x as f32, 0.0, map_ref.y as f32)
+                });
+            },
+            (false, true) => {
+                current_area.loaded_maps.remove(&map_path);
+                commands.trigger(DespawnMap { file: map_path });
+            },
+            _ => {}
         }
     }
 }
@@ -172,7 +172,7 @@ pub fn despawn_area_locals(
 /// An [`Entity`] that keeps the map it touches loaded.
 /// An AABB surrounds a streamer.
 /// If this AABB touches a particular map, it will load or remain loaded.
-/// If it stops touching a map, it will unload.
+/// If it stops touching a map, it will unload, unless another streamer touches it.
 #[derive(Component, Copy, Clone, PartialEq, Default, Debug)]
 pub struct AreaStreamer {
     pub size: Vec2,
@@ -54,7 +54,7 @@ impl Default for GameCameraBundle {
         camera.color_grading.global.post_saturation = 1.1;
         camera.projection.perspective = PerspectiveProjection { near: 16.0, ..default() };
         camera.projection.orthographic.far = 10000.0;
-        camera.projection.orthographic.scale = 1.0/2.0;
+        camera.projection.orthographic.scale = camera.game_camera.min_scale;
         camera.transform = Transform::from_xyz(128.0, 256.0, 256.0).looking_to(Vec3::new(0.0, -1.0, -1.0), Vec3::Y);
         camera.tonemapping = Tonemapping::None;
         camera
@@ -66,13 +66,19 @@ impl Default for GameCameraBundle {
 pub struct GameCamera {
     pub target: Option<CameraTarget>,
     pub offset: Vec3,
+    /// Space kept between the targets and the edges of the view, in world units.
+    pub margin: Vec2,
+    /// Orthographic scale used while all targets fit in view. Zooms out from there when they do not.
+    pub min_scale: f32,
 }
 
 impl Default for GameCamera {
     fn default() -> Self {
         Self {
-            target: Some(CameraTarget::Player),
+            target: Some(CameraTarget::Players),
             offset: Vec3::new(0.0, 400.0, 400.0),
+            margin: Vec2::new(64.0, 64.0),
+            min_scale: 1.0/2.0,
         }
     }
 }
@@ -80,7 +86,8 @@ impl Default for GameCamera {
 /// Which target a [`GameCamera`] should follow.
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum CameraTarget {
-    Player,
+    /// All players, zooming out to keep them in view.
+    Players,
 }
 
 
@@ -162,25 +169,48 @@ impl CameraProjection for DualProjection {
 }
 
 pub fn follow_target(
-    mut cameras: Query<(&mut GameCamera, &mut Transform), Without<Flycam>>,
+    mut cameras: Query<(&GameCamera, &mut Transform, &mut DualProjection), Without<Flycam>>,
     targets: Query<&Transform, Without<GameCamera>>,
     entity_index: Res<EntityIndex>,
 ) {
-    for (mut game_camera, mut cam_transf) in &mut cameras {
-        let target_entity = match game_camera.target {
-            Some(CameraTarget::Player)          => entity_index.player,
-            _                                   => None,
+    for (game_camera, mut cam_transf, mut cam_proj) in &mut cameras {
+        let target_entities = match game_camera.target {
+            Some(CameraTarget::Players)         => entity_index.players.as_slice(),
+            _                                   => &[],
         };
-        let Some(target_entity) = target_entity else { continue };
-        let Ok(target_transf) = targets.get(target_entity) else {
-            game_camera.target = None;
-            continue;
+        let Some(bounds) = screen_bounds(target_entities.iter().filter_map(|entity| targets.get(*entity).ok())) else { continue };
+
+        // Zooms out until the bounds fit in view, but never closer than min_scale.
+        let ortho = &cam_proj.orthographic;
+        let view_size = ortho.area.size() / ortho.scale;
+        let needed_size = bounds.size() + game_camera.margin * 2.0;
+        let zoom = match view_size.cmpgt(Vec2::ZERO).all() {
+            true => (needed_size / (view_size * game_camera.min_scale)).max_element().max(1.0),
+            false => 1.0,
         };
-        cam_transf.translation = target_transf.translation + game_camera.offset;
-        cam_transf.look_at(target_transf.translation, Vec3::Y);
+        let scale = game_camera.min_scale * zoom;
+        if cam_proj.orthographic.scale != scale {
+            cam_proj.orthographic.scale = scale;
+        }
+
+        let center = bounds.center();
+        let target_pos = Vec3::new(center.x, 0.0, center.y);
+        cam_transf.translation = target_pos + game_camera.offset * zoom;
+        cam_transf.look_at(target_pos, Vec3::Y);
     }
 }
 
+/// Bounds of a set of transforms as seen by a [`GameCamera`] looking down at 45 degrees, or [`None`] if empty.
+/// Heights are folded into z, so the bounds are in ground units: x, then z - y.
+fn screen_bounds<'a>(transforms: impl Iterator<Item = &'a Transform>) -> Option<Rect> {
+    transforms
+        .map(|transf| Vec2::new(transf.translation.x, transf.translation.z - transf.translation.y))
+        .fold(None, |bounds: Option<Rect>, point| match bounds {
+            Some(bounds) => Some(bounds.union_point(point)),
+            None => Some(Rect::from_corners(point, point)),
+        })
+}
+
 
 pub fn update_flycam(
     mut flycams: Query<(&mut Flycam, &mut Transform)>,
@@ -277,4 +307,45 @@ pub fn handle_disable_debug(
         cam_proj.kind = ProjectionKind::default();
         commands.entity(cam_id).remove::<Flycam>();
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+    use super::*;
+
+    /// Spawns a camera with an 800x600 window, and players at the positions specified.
+    fn setup(positions: &[Vec3]) -> (World, Entity) {
+        let mut world = World::new();
+        let players = positions.iter().map(|pos| world.spawn(Transform::from_translation(*pos)).id()).collect();
+        world.insert_resource(EntityIndex { players, ..default() });
+        let mut projection = DualProjection::default();
+        projection.orthographic.scale = 1.0/2.0;
+        projection.orthographic.update(800.0, 600.0);
+        let camera = world.spawn((GameCamera::default(), Transform::default(), projection)).id();
+        (world, camera)
+    }
+
+    fn camera_scale(world: &World, camera: Entity) -> f32 {
+        world.get::<DualProjection>(camera).unwrap().orthographic.scale
+    }
+
+    #[test]
+    fn close_players_keep_default_zoom() {
+        let (mut world, camera) = setup(&[Vec3::new(0.0, 0.0, 0.0), Vec3::new(32.0, 0.0, 0.0)]);
+        world.run_system_once(follow_target);
+        assert_eq!(1.0/2.0, camera_scale(&world, camera));
+        let look = world.get::<Transform>(camera).unwrap().translation - GameCamera::default().offset;
+        assert_eq!(Vec3::new(16.0, 0.0, 0.0), look);
+    }
+
+    #[test]
+    fn distant_players_zoom_out_to_fit() {
+        let (mut world, camera) = setup(&[Vec3::new(0.0, 0.0, 0.0), Vec3::new(1000.0, 0.0, 0.0)]);
+        world.run_system_once(follow_target);
+        let scale = camera_scale(&world, camera);
+        let margin = GameCamera::default().margin.x;
+        assert!(800.0 * scale >= 1000.0 + margin * 2.0, "Players don't fit at scale {scale}");
+        assert!(scale < 2.0, "Zoomed out further than needed: {scale}");
+    }
+}
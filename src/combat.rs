@@ -0,0 +1,126 @@
+//! Hitboxes, hurtboxes and damage.
+
+use std::time::Duration;
+use bevy::prelude::*;
+use smallvec::SmallVec;
+use crate::area::AreaLocal;
+use messages::Damaged;
+
+
+/// A short-lived region that damages [`Hurtbox`]es it overlaps.
+/// Each hurtbox is damaged at most once per hitbox.
+/// Despawns when its lifetime runs out.
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct Hitbox {
+    /// Entity that spawned the hitbox. Never damaged by it.
+    pub owner: Entity,
+    /// Size of the hitbox's AABB, centered on its translation.
+    pub size: Vec3,
+    pub damage: f32,
+    /// Strength of the impulse that pushes damaged characters away.
+    pub knockback: f32,
+    /// Time remaining before the hitbox despawns.
+    pub lifetime: Duration,
+    /// Entities already damaged by this hitbox.
+    pub hits: SmallVec<[Entity; 4]>,
+}
+
+impl Hitbox {
+    pub fn new(owner: Entity, size: Vec3, damage: f32, lifetime: Duration) -> Self {
+        Self { owner, size, damage, knockback: 0.0, lifetime, hits: SmallVec::new() }
+    }
+
+    pub fn with_knockback(mut self, knockback: f32) -> Self {
+        self.knockback = knockback;
+        self
+    }
+}
+
+/// A region of an entity that can be damaged by [`Hitbox`]es.
+#[derive(Component, Copy, Clone, PartialEq, Default, Debug)]
+pub struct Hurtbox {
+    /// Size of the hurtbox's AABB.
+    pub size: Vec3,
+    /// Offset of the hurtbox's AABB from the entity's translation.
+    pub offset: Vec3,
+}
+
+/// Spawns a [`Hitbox`] at a position.
+pub fn spawn_hitbox(commands: &mut Commands, hitbox: Hitbox, position: Vec3) -> Entity {
+    let size = hitbox.size;
+    commands.spawn((
+        Name::new("hitbox"),
+        hitbox,
+        SpatialBundle::from_transform(Transform::from_translation(position)),
+        AreaLocal { size: Vec2::new(size.x, size.y + size.z) },
+    )).id()
+}
+
+/// Damages hurtboxes that overlap hitboxes, then despawns expired hitboxes.
+pub fn resolve_hitboxes(
+    mut hitboxes: Query<(Entity, &mut Hitbox, &GlobalTransform)>,
+    hurtboxes: Query<(Entity, &Hurtbox, &GlobalTransform)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (hitbox_id, mut hitbox, hitbox_transf) in &mut hitboxes {
+        let hitbox_pos = hitbox_transf.translation();
+        for (hurtbox_id, hurtbox, hurtbox_transf) in &hurtboxes {
+            if hurtbox_id == hitbox.owner || hitbox.hits.contains(&hurtbox_id) { continue };
+            let hurtbox_pos = hurtbox_transf.translation() + hurtbox.offset;
+            if !overlaps(hitbox_pos, hitbox.size, hurtbox_pos, hurtbox.size) { continue };
+            hitbox.hits.push(hurtbox_id);
+            let damaged = Damaged {
+                target: hurtbox_id,
+                attacker: hitbox.owner,
+                amount: hitbox.damage,
+                knockback: hitbox.knockback,
+                direction: (hurtbox_pos - hitbox_pos).normalize_or_zero(),
+            };
+            commands.trigger_targets(damaged, hurtbox_id);
+        }
+        hitbox.lifetime = hitbox.lifetime.saturating_sub(time.delta());
+        if hitbox.lifetime.is_zero() {
+            commands.entity(hitbox_id).despawn_recursive();
+        }
+    }
+}
+
+/// Draws hitboxes in red, and hurtboxes in green.
+pub fn draw_hitboxes(
+    hitboxes: Query<(&Hitbox, &GlobalTransform)>,
+    hurtboxes: Query<(&Hurtbox, &GlobalTransform)>,
+    mut gizmos: Gizmos,
+) {
+    for (hitbox, transf) in &hitboxes {
+        let transf = Transform::from_translation(transf.translation()).with_scale(hitbox.size);
+        gizmos.cuboid(transf, Color::srgb(1.0, 0.0, 0.0));
+    }
+    for (hurtbox, transf) in &hurtboxes {
+        let transf = Transform::from_translation(transf.translation() + hurtbox.offset).with_scale(hurtbox.size);
+        gizmos.cuboid(transf, Color::srgb(0.0, 1.0, 0.0));
+    }
+}
+
+fn overlaps(a_pos: Vec3, a_size: Vec3, b_pos: Vec3, b_size: Vec3) -> bool {
+    let dist = (a_pos - b_pos).abs();
+    let max_dist = (a_size + b_size) / 2.0;
+    dist.x <= max_dist.x && dist.y <= max_dist.y && dist.z <= max_dist.z
+}
+
+
+pub mod messages {
+    use bevy::prelude::*;
+
+    /// Fired when a [`Hitbox`](super::Hitbox) overlaps a [`Hurtbox`](super::Hurtbox).
+    /// Targets the entity that was damaged.
+    #[derive(Event, Copy, Clone, PartialEq, Debug)]
+    pub struct Damaged {
+        pub target: Entity,
+        pub attacker: Entity,
+        pub amount: f32,
+        pub knockback: f32,
+        /// Direction from the hitbox to the hurtbox.
+        pub direction: Vec3,
+    }
+}
@@ -1,4 +1,8 @@
 use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::EguiContexts;
+use bevy_inspector_egui::egui;
+use crate::action::{ActionId, ActionQueue};
+use crate::messages::{LoadGame, RunAction, SaveGame};
 
 #[derive(States, Clone, Eq, PartialEq, Hash, Default, Debug)]
 pub enum DebugStates {
@@ -18,4 +22,74 @@ pub fn toggle_debug(
             DebugStates::Disabled => next_state.set(DebugStates::Enabled),
         };
     }
-}
\ No newline at end of file
+}
+
+/// Quick saves with F5, and quick loads with F9.
+/// Uses its own slot, separate from the one the equipment menu saves to.
+pub fn quick_save(keyboard: Res<ButtonInput<KeyCode>>, mut commands: Commands) {
+    if keyboard.just_pressed(KeyCode::F5) {
+        commands.trigger(SaveGame { slot: "quick".into() });
+    }
+    if keyboard.just_pressed(KeyCode::F9) {
+        commands.trigger(LoadGame { slot: "quick".into() });
+    }
+}
+
+/// Panel listing every [`ActionQueue`], with controls for skipping, quitting and restarting them.
+/// Only queues started with an [`ActionId`] can be restarted.
+pub fn render_action_debugger(
+    mut contexts: EguiContexts,
+    mut queues: Query<(Entity, &mut ActionQueue, Option<&ActionId>, Option<&Name>)>,
+    mut commands: Commands,
+) {
+    egui::Window::new("Action Queues").show(contexts.ctx_mut(), |ui| {
+        if queues.is_empty() {
+            ui.label("No action queues");
+        }
+        for (entity, mut queue, id, name) in &mut queues {
+            let Some(inner) = queue.get() else { continue };
+            let title = match (id, name) {
+                (_, Some(name)) => format!("{name} ({entity})"),
+                (Some(id), None) => format!("{id} ({entity})"),
+                (None, None) => format!("{entity}"),
+            };
+            let (mut skip, mut quit, mut restart) = (false, false, false);
+            egui::CollapsingHeader::new(title)
+                .id_source(entity)
+                .default_open(true)
+                .show(ui, |ui| {
+                    ui.label(format!("Elapsed: {:.2}s", inner.elapsed().as_secs_f32()));
+                    match inner.running_action() {
+                        Some(action) => {
+                            ui.label(format!("Running: {} ({:.2}s)", action.name(), inner.action_elapsed().as_secs_f32()));
+                            if let Some(progress) = action.progress() {
+                                ui.add(egui::ProgressBar::new(progress).show_percentage());
+                            }
+                        },
+                        None => { ui.label("Running: none"); },
+                    }
+                    ui.label("Remaining:");
+                    for action in inner.remaining_actions() {
+                        ui.label(format!("    {}", action.name()));
+                    }
+                    ui.horizontal(|ui| {
+                        skip = ui.button("Skip").clicked();
+                        quit = ui.button("Quit").clicked();
+                        if id.is_some() {
+                            restart = ui.button("Restart").clicked();
+                        }
+                    });
+                });
+            if skip {
+                queue.skip();
+            }
+            if quit {
+                queue.quit();
+            }
+            if let (true, Some(id)) = (restart, id) {
+                queue.quit();
+                commands.trigger(RunAction(id.clone()));
+            }
+        }
+    });
+}
@@ -0,0 +1,38 @@
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::asset::io::Reader;
+use thiserror::*;
+
+use super::Dialogue;
+
+/// Loads a [`Dialogue`].
+#[derive(Default)]
+pub struct DialogueLoader;
+impl AssetLoader for DialogueLoader {
+
+    type Asset = Dialogue;
+    type Settings = ();
+    type Error = DialogueLoadError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Dialogue, DialogueLoadError> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes).await?;
+        let dialogue: Dialogue = ron::de::from_bytes(&bytes)?;
+        Ok(dialogue)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["dialogue.ron"]
+    }
+}
+
+#[derive(Error, Debug)]
+#[error(transparent)]
+pub enum DialogueLoadError {
+    IOError(#[from] std::io::Error),
+    RonError(#[from] ron::error::SpannedError),
+}
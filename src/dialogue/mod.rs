@@ -0,0 +1,424 @@
+//! NPC speech shown in a text box that types itself out.
+//!
+//! Example:
+//! ```ron
+//! Dialogue(
+//!     start: "greet",
+//!     nodes: {
+//!         "greet": (
+//!             lines: [(speaker: Some("Guide"), text: "Hello, {player_name}!")],
+//!             choices: [
+//!                 (text: "Hi!", next: Some("bye"), set: {"mood": "happy"}),
+//!                 (text: "...", next: None),
+//!             ],
+//!         ),
+//!         "bye": (lines: [(speaker: Some("Guide"), text: "Take care.")]),
+//!     },
+//! )
+//! ```
+
+mod loader;
+
+pub use loader::*;
+
+use std::time::Duration;
+use bevy::prelude::*;
+use bevy::asset::LoadState;
+use bevy::utils::HashMap;
+use serde::Deserialize;
+use messages::{EndDialogue, StartDialogue};
+use crate::action::{Action, EndEnv, RunEnv, RunStatus, StartEnv};
+use crate::flags::{Flag, Flags};
+use crate::input::VButtons;
+use crate::player::{buttons, Player};
+use crate::ui::{c_frame, c_text, create_node, Dsl, ImageW, NodeW, SlicedImageW, TextW};
+
+const FONT_SIZE: f32 = 14.0;
+const CHARS_PER_SEC: f32 = 40.0;
+const PORTRAIT_SIZE: f32 = 48.0;
+
+
+/// A graph of dialogue nodes.
+#[derive(Asset, TypePath, Deserialize, Clone, Default, Debug)]
+pub struct Dialogue {
+    /// Node to begin at.
+    pub start: String,
+    pub nodes: HashMap<String, DialogueNode>,
+}
+
+/// Lines spoken in sequence, followed by an optional set of choices.
+#[derive(Deserialize, Clone, Default, Debug)]
+pub struct DialogueNode {
+    pub lines: Vec<DialogueLine>,
+    /// Choices presented after the last line.
+    #[serde(default)]
+    pub choices: Vec<DialogueChoice>,
+    /// Conditional jumps taken after the last line when there are no choices.
+    /// First matching branch wins.
+    #[serde(default)]
+    pub branches: Vec<DialogueBranch>,
+    /// Node to go to when there are no choices, and no branch matched.
+    /// Dialogue ends if [`None`].
+    #[serde(default)]
+    pub next: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Default, Debug)]
+pub struct DialogueLine {
+    #[serde(default)]
+    pub speaker: Option<String>,
+    /// Path to portrait image.
+    #[serde(default)]
+    pub portrait: Option<String>,
+    /// Text to type out. Occurrences of {flag} are replaced with the flag's value.
+    pub text: String,
+}
+
+#[derive(Deserialize, Clone, Default, Debug)]
+pub struct DialogueChoice {
+    pub text: String,
+    /// Node to go to. Dialogue ends if [`None`].
+    #[serde(default)]
+    pub next: Option<String>,
+    /// Flags to set when chosen.
+    #[serde(default)]
+    pub set: HashMap<String, Flag>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct DialogueBranch {
+    pub flag: String,
+    pub equals: Flag,
+    pub next: String,
+}
+
+/// Dialogue currently being shown.
+/// Only one dialogue can be shown at a time.
+#[derive(Resource, Debug)]
+pub struct ActiveDialogue {
+    /// Root UI entity.
+    pub entity: Entity,
+    dialogue: Dialogue,
+    node: String,
+    line_idx: usize,
+    chars_shown: f32,
+    stage: DialogueStage,
+}
+
+impl ActiveDialogue {
+    fn current_node(&self) -> Option<&DialogueNode> {
+        self.dialogue.nodes.get(&self.node)
+    }
+
+    fn current_line(&self) -> Option<&DialogueLine> {
+        self.current_node()?.lines.get(self.line_idx)
+    }
+
+    fn goto(&mut self, node: &str) -> bool {
+        if !self.dialogue.nodes.contains_key(node) {
+            bevy::log::warn!("Dialogue node '{node}' not found");
+            return false;
+        }
+        self.node = node.to_owned();
+        self.line_idx = 0;
+        self.chars_shown = 0.0;
+        self.stage = DialogueStage::Typing;
+        true
+    }
+}
+
+/// What an [`ActiveDialogue`] is currently doing.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum DialogueStage {
+    /// Typing out the current line.
+    Typing,
+    /// Current line fully shown. Waiting for confirm.
+    Waiting,
+    /// Selecting one of the current node's choices.
+    Choosing { selected: usize },
+}
+
+/// Marks the text entity of the dialogue box.
+#[derive(Component, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct DialogueText;
+
+
+/// Shows a dialogue, replacing any already shown.
+pub fn start_dialogue(
+    trigger: Trigger<StartDialogue>,
+    dialogues: Res<Assets<Dialogue>>,
+    active_dialogue: Option<Res<ActiveDialogue>>,
+    mut commands: Commands,
+) {
+    let Some(dialogue) = dialogues.get(&trigger.event().dialogue) else {
+        bevy::log::warn!("Dialogue not loaded");
+        return;
+    };
+    if let Some(active_dialogue) = active_dialogue {
+        commands.entity(active_dialogue.entity).despawn_recursive();
+    }
+    let entity = commands.spawn((Name::new("dialogue"), create_node(c_dialogue_root))).id();
+    commands.insert_resource(ActiveDialogue {
+        entity,
+        dialogue: dialogue.clone(),
+        node: dialogue.start.clone(),
+        line_idx: 0,
+        chars_shown: 0.0,
+        stage: DialogueStage::Typing,
+    });
+}
+
+/// Hides the dialogue being shown.
+pub fn end_dialogue(
+    _trigger: Trigger<EndDialogue>,
+    active_dialogue: Option<Res<ActiveDialogue>>,
+    mut commands: Commands,
+) {
+    let Some(active_dialogue) = active_dialogue else { return };
+    commands.entity(active_dialogue.entity).despawn_recursive();
+    commands.remove_resource::<ActiveDialogue>();
+}
+
+/// Types out text, and advances the dialogue when players press confirm.
+pub fn update_dialogue(
+    mut active_dialogue: ResMut<ActiveDialogue>,
+    mut flags: ResMut<Flags>,
+    players: Query<&VButtons, With<Player>>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    let just_pressed = |button| players.iter().any(|vbuttons| vbuttons.just_pressed(button));
+    let confirm = just_pressed(buttons::CONFIRM);
+    let (up, down) = (just_pressed(buttons::UP), just_pressed(buttons::DOWN));
+
+    // Types out text without triggering a re-render
+    let dialogue = active_dialogue.bypass_change_detection();
+    let (Some(node), Some(line)) = (dialogue.current_node().cloned(), dialogue.current_line()) else {
+        commands.trigger(EndDialogue);
+        return;
+    };
+    let line_len = flags.substitute(&line.text).chars().count() as f32;
+    if dialogue.stage == DialogueStage::Typing {
+        dialogue.chars_shown += CHARS_PER_SEC * time.delta_seconds();
+        if dialogue.chars_shown >= line_len {
+            dialogue.stage = DialogueStage::Waiting;
+        }
+    }
+
+    let mut changed = false;
+    match dialogue.stage {
+        // Skips to the end of the line
+        DialogueStage::Typing => if confirm {
+            dialogue.chars_shown = line_len;
+            dialogue.stage = DialogueStage::Waiting;
+        },
+        // Advances to next line, choices, or node
+        DialogueStage::Waiting => if confirm {
+            if dialogue.line_idx + 1 < node.lines.len() {
+                dialogue.line_idx += 1;
+                dialogue.chars_shown = 0.0;
+                dialogue.stage = DialogueStage::Typing;
+            }
+            else if !node.choices.is_empty() {
+                dialogue.stage = DialogueStage::Choosing { selected: 0 };
+            }
+            else {
+                let next = node.branches.iter()
+                    .find(|branch| flags.get(&branch.flag) == Some(&branch.equals))
+                    .map(|branch| &branch.next)
+                    .or(node.next.as_ref());
+                match next {
+                    Some(next) if dialogue.goto(next) => {},
+                    _ => commands.trigger(EndDialogue),
+                }
+            }
+            changed = true;
+        },
+        // Moves selection, or picks a choice
+        DialogueStage::Choosing { selected } => {
+            let choices = &node.choices;
+            if confirm {
+                let choice = &choices[selected];
+                for (name, value) in &choice.set {
+                    flags.set(name.clone(), value.clone());
+                }
+                match &choice.next {
+                    Some(next) if dialogue.goto(next) => {},
+                    _ => commands.trigger(EndDialogue),
+                }
+                changed = true;
+            }
+            else if up || down {
+                let len = choices.len();
+                let selected = match up {
+                    true => (selected + len - 1) % len,
+                    false => (selected + 1) % len,
+                };
+                dialogue.stage = DialogueStage::Choosing { selected };
+                changed = true;
+            }
+        },
+    }
+    if changed {
+        active_dialogue.set_changed();
+    }
+}
+
+/// Writes the portion of the current line that has been typed out.
+pub fn type_dialogue_text(
+    active_dialogue: Res<ActiveDialogue>,
+    flags: Res<Flags>,
+    mut texts: Query<&mut Text, With<DialogueText>>,
+) {
+    let Some(line) = active_dialogue.current_line() else { return };
+    let shown = flags
+        .substitute(&line.text)
+        .chars()
+        .take(active_dialogue.chars_shown as usize)
+        .collect::<String>();
+    for mut text in &mut texts {
+        let Some(section) = text.sections.first_mut() else { continue };
+        if section.value != shown {
+            section.value = shown.clone();
+        }
+    }
+}
+
+/// Rebuilds the dialogue box when the line or choices change.
+pub fn render_dialogue(
+    active_dialogue: Res<ActiveDialogue>,
+    flags: Res<Flags>,
+    assets: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    let Some(line) = active_dialogue.current_line() else { return };
+    commands.entity(active_dialogue.entity).despawn_descendants();
+    let d = &mut Dsl::new(&mut commands, active_dialogue.entity);
+    let font = assets.load::<Font>("fonts/Retro Gaming.ttf");
+    let frame = assets.load::<Image>("ui/UI_Paper_Frame_01_Standard.png");
+
+    SlicedImageW::new(&frame).class(c_frame).class(c_dialogue_frame).begin(d);
+        if let Some(portrait) = &line.portrait {
+            ImageW::new(&assets.load(portrait)).class(c_portrait).spawn(d);
+        }
+        NodeW::cls(c_column).begin(d);
+            if let Some(speaker) = &line.speaker {
+                TextW::new(speaker).class(c_text).class(c_speaker).font(&font).font_size(FONT_SIZE).spawn(d);
+            }
+            TextW::new("").class(c_text).font(&font).font_size(FONT_SIZE).spawn(d);
+            d.commands.entity(d.last()).insert(DialogueText);
+            if let DialogueStage::Choosing { selected } = active_dialogue.stage {
+                let choices = &active_dialogue.current_node().unwrap().choices;
+                for (choice_idx, choice) in choices.iter().enumerate() {
+                    let marker = if choice_idx == selected { "> " } else { "  " };
+                    let text = format!("{marker}{}", flags.substitute(&choice.text));
+                    TextW::new(text).class(c_text).font(&font).font_size(FONT_SIZE).spawn(d);
+                }
+            }
+        NodeW::end(d);
+    SlicedImageW::end(d);
+}
+
+
+/// Shows a [`Dialogue`] asset, finishing when it ends.
+/// Ends the dialogue early if the queue quits.
+pub struct RunDialogue {
+    path: String,
+    handle: Option<Handle<Dialogue>>,
+    shown: bool,
+}
+
+impl RunDialogue {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self { path: path.into(), handle: None, shown: false }
+    }
+}
+
+impl Action for RunDialogue {
+
+    fn start(&mut self, env: &mut StartEnv) {
+        let assets = env.world.resource::<AssetServer>();
+        self.handle = Some(assets.load(self.path.clone()));
+    }
+
+    fn run(&mut self, env: &mut RunEnv) -> RunStatus {
+        let handle = self.handle.clone().unwrap();
+        if !self.shown {
+            if env.world.resource::<Assets<Dialogue>>().contains(&handle) {
+                env.world.trigger(StartDialogue { dialogue: handle });
+                self.shown = true;
+                return RunStatus::Running;
+            }
+            let assets = env.world.resource::<AssetServer>();
+            if let Some(LoadState::Failed(err)) = assets.get_load_state(&handle) {
+                bevy::log::error!("Failed to load dialogue '{}': {err}", self.path);
+                return RunStatus::Finished { time_consumed: Duration::ZERO };
+            }
+            return RunStatus::Running;
+        }
+        match env.world.contains_resource::<ActiveDialogue>() {
+            true => RunStatus::Running,
+            false => RunStatus::Finished { time_consumed: Duration::ZERO },
+        }
+    }
+
+    fn end(&mut self, env: &mut EndEnv) {
+        if self.shown && env.world.contains_resource::<ActiveDialogue>() {
+            env.world.trigger(EndDialogue);
+        }
+    }
+
+    fn name(&self) -> String {
+        format!("RunDialogue({})", self.path)
+    }
+}
+
+
+fn c_dialogue_root(node: &mut NodeBundle) {
+    node.style.width = Val::Percent(100.0);
+    node.style.height = Val::Percent(100.0);
+    node.style.justify_content = JustifyContent::End;
+    node.style.align_items = AlignItems::Center;
+    node.style.flex_direction = FlexDirection::Column;
+    node.style.padding = UiRect::bottom(Val::Px(10.0));
+}
+
+fn c_dialogue_frame(image: &mut SlicedImageW) {
+    let style = &mut image.image.style;
+    style.width = Val::Px(400.0);
+    style.min_height = Val::Px(80.0);
+    style.flex_direction = FlexDirection::Row;
+    style.align_items = AlignItems::Start;
+    style.padding = UiRect::all(Val::Px(16.0));
+}
+
+fn c_portrait(image: &mut ImageW) {
+    image.style.width = Val::Px(PORTRAIT_SIZE);
+    image.style.height = Val::Px(PORTRAIT_SIZE);
+    image.style.margin = UiRect::right(Val::Px(8.0));
+}
+
+fn c_column(node: &mut NodeW) {
+    node.style.flex_direction = FlexDirection::Column;
+    node.style.flex_grow = 1.0;
+}
+
+fn c_speaker(text: &mut TextW) {
+    text.style.margin = UiRect::bottom(Val::Px(4.0));
+}
+
+
+pub mod messages {
+    use bevy::prelude::*;
+    use super::Dialogue;
+
+    /// Shows a loaded [`Dialogue`](super::Dialogue).
+    #[derive(Event, Clone, Eq, PartialEq, Debug)]
+    pub struct StartDialogue {
+        pub dialogue: Handle<Dialogue>,
+    }
+
+    /// Hides the dialogue being shown.
+    #[derive(Event, Copy, Clone, Eq, PartialEq, Default, Debug)]
+    pub struct EndDialogue;
+}
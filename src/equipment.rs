@@ -13,6 +13,7 @@ pub struct Equipment {
     pub outfit: Option<Equippable>,
     pub hands: Option<Equippable>,
     pub feet: Option<Equippable>,
+    pub weapon: Option<Equippable>,
 }
 
 /// Type of equippable.
@@ -22,6 +23,7 @@ pub enum Equippable {
     Hair(Hair),
     Hat(Hat),
     Outfit(Outfit),
+    Weapon(Weapon),
 }
 
 impl Default for Equippable {
@@ -42,6 +44,7 @@ impl Equippable {
             Self::Outfit(Outfit::Casual3)                                       => EquippableInfo { name: "Casual 3", image: "player/outfit/casual_3.png", ..default() },
             Self::Outfit(Outfit::Casual4)                                       => EquippableInfo { name: "Casual 4", image: "player/outfit/casual_4.png", ..default() },
             Self::Outfit(Outfit::Casual5)                                       => EquippableInfo { name: "Casual 5", image: "player/outfit/casual_5.png", ..default() },
+            Self::Weapon(Weapon::WoodenAxe)                                     => EquippableInfo { name: "Wooden Axe", image: "player/weapons/char_a_p1C_6tla_wood_v01.png", ..default() },
         }
     }
 }
@@ -118,10 +121,18 @@ pub enum Outfit {
     Casual5,
 }
 
+#[derive(Reflect, Clone, PartialEq, Debug, Default)]
+#[reflect(Default)]
+pub enum Weapon {
+    #[default]
+    WoodenAxe,
+}
+
 
 const OUTFIT_OFFSET: f32 = 0.001;
 const HAIR_OFFSET: f32 = 0.002;
 const HAT_OFFSET: f32 = 0.003;
+const WEAPON_OFFSET: f32 = 0.004;
 
 /// Spawns child equipment entities whenever an entity's equipment changes.
 pub fn spawn_equipment_entities(
@@ -165,6 +176,9 @@ pub fn spawn_equipment_entities(
         if let Some(ref item) = equip.outfit {
             spawn_equippable(equip_id, item, OUTFIT_OFFSET, &mut commands);
         }
+        if let Some(ref item) = equip.weapon {
+            spawn_equippable(equip_id, item, WEAPON_OFFSET, &mut commands);
+        }
     }
 }
 
@@ -0,0 +1,173 @@
+//! Persistent flags and variables that drive story progression.
+
+use std::fmt;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+
+/// Value of a flag.
+/// Deserializes from a plain bool, integer or string.
+#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+#[serde(untagged)]
+pub enum Flag {
+    Bool(bool),
+    Int(i64),
+    Str(String),
+}
+
+impl Flag {
+    /// Whether the flag counts as "set".
+    /// False, zero and empty strings are not set.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Flag::Bool(value) => *value,
+            Flag::Int(value) => *value != 0,
+            Flag::Str(value) => !value.is_empty(),
+        }
+    }
+}
+
+impl fmt::Display for Flag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Flag::Bool(value) => write!(f, "{value}"),
+            Flag::Int(value) => write!(f, "{value}"),
+            Flag::Str(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+impl From<bool> for Flag {
+    fn from(value: bool) -> Self { Self::Bool(value) }
+}
+
+impl From<i64> for Flag {
+    fn from(value: i64) -> Self { Self::Int(value) }
+}
+
+impl From<&str> for Flag {
+    fn from(value: &str) -> Self { Self::Str(value.to_owned()) }
+}
+
+impl From<String> for Flag {
+    fn from(value: String) -> Self { Self::Str(value) }
+}
+
+
+/// Game-wide flags and variables keyed by name.
+/// Persists for the entire session, and is included in save games.
+#[derive(Resource, Serialize, Deserialize, Clone, Eq, PartialEq, Default, Debug)]
+pub struct Flags(HashMap<String, Flag>);
+
+impl Flags {
+
+    pub fn get(&self, name: &str) -> Option<&Flag> {
+        self.0.get(name)
+    }
+
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<Flag>) {
+        self.0.insert(name.into(), value.into());
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Flag> {
+        self.0.remove(name)
+    }
+
+    /// True if the flag exists and is truthy.
+    pub fn is_set(&self, name: &str) -> bool {
+        self.get(name).is_some_and(Flag::is_truthy)
+    }
+
+    /// Value of an integer flag.
+    /// Zero if missing or not an integer.
+    pub fn int(&self, name: &str) -> i64 {
+        match self.get(name) {
+            Some(Flag::Int(value)) => *value,
+            _ => 0,
+        }
+    }
+
+    /// Adds to an integer flag, treating it as zero if missing or not an integer.
+    /// Returns the new value.
+    pub fn add(&mut self, name: &str, amount: i64) -> i64 {
+        let value = self.int(name) + amount;
+        self.set(name, value);
+        value
+    }
+
+    /// Replaces occurrences of {flag} with the flag's value.
+    /// Unknown flags are left as is.
+    /// Substituted values are not scanned again.
+    pub fn substitute(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(open) = rest.find('{') {
+            result.push_str(&rest[..open]);
+            let after_open = &rest[open + 1..];
+            let name = after_open
+                .find('}')
+                .map(|close| &after_open[..close])
+                .filter(|name| !name.contains('{'));
+            match name.and_then(|name| self.get(name).map(|value| (name, value))) {
+                Some((name, value)) => {
+                    result.push_str(&value.to_string());
+                    rest = &after_open[name.len() + 1..];
+                },
+                None => {
+                    result.push('{');
+                    rest = after_open;
+                },
+            }
+        }
+        result.push_str(rest);
+        result
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Flag)> {
+        self.0.iter()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags() -> Flags {
+        let mut flags = Flags::default();
+        flags.set("name", "Ann");
+        flags.set("coins", 3);
+        flags.set("brace", "{coins}");
+        flags
+    }
+
+    #[test]
+    fn substitute_replaces_known_flags() {
+        assert_eq!("Ann has 3 coins", flags().substitute("{name} has {coins} coins"));
+        assert_eq!("3 3", flags().substitute("{coins} {coins}"));
+    }
+
+    #[test]
+    fn substitute_leaves_unknown_flags() {
+        assert_eq!("{missing} Ann", flags().substitute("{missing} {name}"));
+        assert_eq!("{Ann {} }", flags().substitute("{{name} {} }"));
+        assert_eq!("Ann {name", flags().substitute("{name} {name"));
+    }
+
+    #[test]
+    fn substitute_does_not_rescan_values() {
+        assert_eq!("{coins}", flags().substitute("{brace}"));
+    }
+
+    #[test]
+    fn flag_round_trips_untagged() {
+        for flag in [Flag::Bool(true), Flag::Int(-7), Flag::Str("7".into()), Flag::Str("true".into())] {
+            let ron = ron::to_string(&flag).unwrap();
+            assert_eq!(flag, ron::from_str::<Flag>(&ron).unwrap());
+        }
+        assert_eq!(Flag::Bool(false), ron::from_str::<Flag>("false").unwrap());
+        assert_eq!(Flag::Int(12), ron::from_str::<Flag>("12").unwrap());
+        assert_eq!(Flag::Str("hi".into()), ron::from_str::<Flag>("\"hi\"").unwrap());
+    }
+}
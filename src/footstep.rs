@@ -0,0 +1,140 @@
+//! Surfaces under characters, footsteps and the dust they kick up.
+
+use std::ops::Range;
+use std::time::Duration;
+use bevy::prelude::*;
+use smallvec::SmallVec;
+use crate::animation::AnimationState;
+use crate::common::CommonAssets;
+use crate::map::Navigation;
+use crate::player::CharacterController;
+use messages::Footstep;
+
+const DUST_LIFETIME: Duration = Duration::from_millis(300);
+const DUST_SIZE: f32 = 2.0;
+const DUST_GROWTH: f32 = 1.5;
+const DUST_RISE: f32 = 4.0;
+
+
+/// Fires [`Footstep`]s at an entity when its walk animations reach particular frames.
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct Footsteps {
+    /// Indices of the walk animations in the entity's animation set. IE: one per direction.
+    pub animations: Range<usize>,
+    /// Frames in which a foot touches the ground.
+    pub frames: SmallVec<[usize; 2]>,
+    /// Animation and frame seen last, so that each frame fires once.
+    prev_frame: Option<(usize, usize)>,
+}
+
+impl Footsteps {
+    pub fn new(animations: Range<usize>, frames: impl IntoIterator<Item = usize>) -> Self {
+        Self {
+            animations,
+            frames: frames.into_iter().collect(),
+            prev_frame: None,
+        }
+    }
+}
+
+/// A puff of dust that rises and fades where a footstep landed.
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct Dust {
+    pub material: Handle<StandardMaterial>,
+    pub elapsed: Duration,
+}
+
+/// Syncs character controllers with the surface under them.
+pub fn update_surfaces(
+    mut characters: Query<(&mut CharacterController, &GlobalTransform)>,
+    navigation: Navigation,
+) {
+    for (mut cc, transf) in &mut characters {
+        let surface = navigation.surface_at(transf.translation()).unwrap_or_default();
+        if cc.surface != surface {
+            cc.surface = surface;
+        }
+    }
+}
+
+/// Fires a [`Footstep`] at entities whose walk animations just reached a footstep frame.
+pub fn emit_footsteps(
+    mut walkers: Query<(Entity, &mut Footsteps, &AnimationState, &GlobalTransform)>,
+    navigation: Navigation,
+    mut commands: Commands,
+) {
+    for (entity, mut footsteps, anim_state, transf) in &mut walkers {
+        let frame = (anim_state.animation_idx, anim_state.frame_idx);
+        if footsteps.prev_frame == Some(frame) { continue };
+        footsteps.prev_frame = Some(frame);
+        if !footsteps.animations.contains(&frame.0) || !footsteps.frames.contains(&frame.1) { continue };
+        let position = transf.translation();
+        let surface = navigation.surface_at(position).unwrap_or_default();
+        commands.trigger_targets(Footstep { surface, position }, entity);
+    }
+}
+
+/// Kicks up dust from footsteps on dusty surfaces.
+pub fn spawn_footstep_dust(
+    trigger: Trigger<Footstep>,
+    common_assets: Res<CommonAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+) {
+    let footstep = trigger.event();
+    let Some(color) = footstep.surface.dust_color() else { return };
+    let material = materials.add(StandardMaterial {
+        base_color: color,
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    });
+    commands.spawn((
+        Name::new("dust"),
+        Dust { material: material.clone(), elapsed: Duration::ZERO },
+        PbrBundle {
+            mesh: common_assets.meshes.sphere.clone(),
+            material,
+            transform: Transform::from_translation(footstep.position).with_scale(Vec3::splat(DUST_SIZE)),
+            ..default()
+        },
+    ));
+}
+
+/// Grows, raises and fades dust, despawning it when it finishes.
+pub fn update_dust(
+    mut dust_q: Query<(Entity, &mut Dust, &mut Transform)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut dust, mut transf) in &mut dust_q {
+        dust.elapsed += time.delta();
+        if dust.elapsed >= DUST_LIFETIME {
+            commands.entity(entity).despawn_recursive();
+            materials.remove(&dust.material);
+            continue;
+        }
+        let t = dust.elapsed.as_secs_f32() / DUST_LIFETIME.as_secs_f32();
+        transf.scale = Vec3::splat(DUST_SIZE * (1.0 + t * DUST_GROWTH));
+        transf.translation.y += DUST_RISE * time.delta_seconds();
+        if let Some(material) = materials.get_mut(&dust.material) {
+            let alpha = material.base_color.alpha();
+            material.base_color.set_alpha(alpha.min(1.0 - t));
+        }
+    }
+}
+
+
+pub mod messages {
+    use bevy::prelude::*;
+    use crate::map::Surface;
+
+    /// Fired when a foot touches the ground.
+    /// Targets the entity that stepped.
+    #[derive(Event, Copy, Clone, PartialEq, Debug)]
+    pub struct Footstep {
+        pub surface: Surface,
+        pub position: Vec3,
+    }
+}
@@ -72,6 +72,24 @@ impl VSticks {
 }
 
 
+/// Device a player is controlled with.
+/// Each device controls at most one player.
+#[derive(Component, Copy, Clone, Eq, PartialEq, Hash, Default, Debug)]
+pub enum InputDevice {
+    #[default]
+    Keyboard,
+    Gamepad(Gamepad),
+}
+
+impl std::fmt::Display for InputDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Keyboard => write!(f, "Keyboard"),
+            Self::Gamepad(gamepad) => write!(f, "Gamepad {}", gamepad.id + 1),
+        }
+    }
+}
+
 /// Maps key presses to virtual button presses on an entity.
 #[derive(Component, Clone, Default, Debug)]
 pub struct KeyboardMapping {
@@ -0,0 +1,181 @@
+//! Talking to NPCs, reading signs, opening chests etc.
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use tiled_parser::PropertyValue;
+use crate::action::ActionId;
+use crate::area::AreaLocal;
+use crate::dialogue::ActiveDialogue;
+use crate::input::VButtons;
+use crate::messages::RunAction;
+use crate::player::{buttons, Facing, Player};
+use crate::ui::{c_frame, c_text, create_node, Dsl, SlicedImageW, TextW};
+use messages::Interacted;
+
+const FONT_SIZE: f32 = 14.0;
+/// Minimum dot product between the direction faced, and the direction to an interactable.
+const FACING_THRESHOLD: f32 = 0.5;
+/// Interactables closer than this can be interacted with regardless of facing.
+const TOUCH_DISTANCE: f32 = 4.0;
+
+
+/// Something a player can interact with when near and facing it.
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct Interactable {
+    /// Max distance the interactable can be interacted with from.
+    pub range: f32,
+    /// Text shown on-screen while targeted.
+    pub prompt: String,
+    /// Action to run when interacted with. IE: "cutscene" or the path to a dialogue.
+    pub action: Option<ActionId>,
+}
+
+impl Default for Interactable {
+    fn default() -> Self {
+        Self {
+            range: 24.0,
+            prompt: "Interact".into(),
+            action: None,
+        }
+    }
+}
+
+/// [`Interactable`] a character would interact with if they pressed interact.
+#[derive(Component, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct InteractionTarget(pub Option<Entity>);
+
+/// On-screen prompt of the [`Interactable`] currently targeted.
+#[derive(Resource, Copy, Clone, Eq, PartialEq, Debug)]
+pub struct InteractPrompt {
+    /// Root UI entity.
+    pub entity: Entity,
+    pub target: Entity,
+}
+
+pub fn spawn_interactable(
+    commands: &mut Commands,
+    position: Vec3,
+    size: Vec3,
+    properties: &HashMap<String, PropertyValue>,
+) {
+    let mut interactable = Interactable::default();
+    for (prop_name, prop_value) in properties {
+        match (prop_name.as_str(), prop_value) {
+            ("prompt", PropertyValue::String(prompt))   => interactable.prompt = prompt.clone(),
+            ("action", PropertyValue::String(action))   => interactable.action = Some(ActionId::new(action)),
+            ("range", PropertyValue::Float(value))      => interactable.range = *value,
+            ("range", PropertyValue::Int(value))        => interactable.range = *value as f32,
+            ("prompt", _) => panic!("Property 'prompt' not a string"),
+            ("action", _) => panic!("Property 'action' not a string"),
+            ("range", _) => panic!("Property 'range' not a number"),
+            _ => {}
+        }
+    }
+    commands.spawn((
+        Name::new("interactable"),
+        interactable,
+        SpatialBundle::from_transform(Transform::from_translation(position)),
+        AreaLocal { size: Vec2::new(size.x, size.y + size.z) },
+    ));
+}
+
+/// Targets the nearest in-range interactable each player is facing.
+pub fn select_interaction_targets(
+    mut players: Query<(&Transform, &Facing, &mut InteractionTarget)>,
+    interactables: Query<(Entity, &Interactable, &GlobalTransform)>,
+) {
+    for (transf, facing, mut target) in &mut players {
+        let facing_dir = facing.0.to_vec3();
+        let nearest = interactables.iter()
+            .filter_map(|(entity, interactable, interactable_transf)| {
+                let offset = interactable_transf.translation() - transf.translation;
+                let offset = Vec3::new(offset.x, 0.0, offset.z);
+                let distance = offset.length();
+                if distance > interactable.range { return None };
+                let facing = distance <= TOUCH_DISTANCE || offset.normalize().dot(facing_dir) >= FACING_THRESHOLD;
+                facing.then_some((entity, distance))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(entity, _)| entity);
+        if target.0 != nearest {
+            target.0 = nearest;
+        }
+    }
+}
+
+/// Interacts with targets when players press interact.
+/// Does nothing while dialogue is shown.
+pub fn interact(
+    players: Query<(Entity, &VButtons, &InteractionTarget), With<Player>>,
+    interactables: Query<&Interactable>,
+    active_dialogue: Option<Res<ActiveDialogue>>,
+    mut commands: Commands,
+) {
+    if active_dialogue.is_some() { return };
+    for (player_id, vbuttons, target) in &players {
+        if !vbuttons.just_pressed(buttons::INTERACT) { continue };
+        let Some(target) = target.0 else { continue };
+        let Ok(interactable) = interactables.get(target) else { continue };
+        commands.trigger_targets(Interacted { interactor: player_id }, target);
+        if let Some(action) = &interactable.action {
+            commands.trigger(RunAction(action.clone()));
+        }
+    }
+}
+
+/// Shows the prompt of the first player's target.
+/// Hidden while dialogue is shown.
+pub fn render_interact_prompt(
+    players: Query<&InteractionTarget, With<Player>>,
+    interactables: Query<&Interactable>,
+    active_dialogue: Option<Res<ActiveDialogue>>,
+    prompt: Option<Res<InteractPrompt>>,
+    assets: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    let target = match active_dialogue {
+        Some(_) => None,
+        None => players.iter().find_map(|target| target.0),
+    };
+    if prompt.as_ref().map(|prompt| prompt.target) == target { return };
+    if let Some(prompt) = prompt {
+        commands.entity(prompt.entity).despawn_recursive();
+        commands.remove_resource::<InteractPrompt>();
+    }
+    let Some(target) = target else { return };
+    let Ok(interactable) = interactables.get(target) else { return };
+
+    let entity = commands.spawn((Name::new("interact_prompt"), create_node(c_prompt_root))).id();
+    let d = &mut Dsl::new(&mut commands, entity);
+    let font = assets.load::<Font>("fonts/Retro Gaming.ttf");
+    let frame = assets.load::<Image>("ui/UI_Paper_Frame_01_Standard.png");
+    SlicedImageW::new(&frame).class(c_frame).class(c_prompt_frame).begin(d);
+        TextW::new(&interactable.prompt).class(c_text).font(&font).font_size(FONT_SIZE).spawn(d);
+    SlicedImageW::end(d);
+    commands.insert_resource(InteractPrompt { entity, target });
+}
+
+
+fn c_prompt_root(node: &mut NodeBundle) {
+    node.style.position_type = PositionType::Absolute;
+    node.style.width = Val::Percent(100.0);
+    node.style.bottom = Val::Px(24.0);
+    node.style.justify_content = JustifyContent::Center;
+}
+
+fn c_prompt_frame(image: &mut SlicedImageW) {
+    let style = &mut image.image.style;
+    style.padding = UiRect::axes(Val::Px(14.0), Val::Px(10.0));
+}
+
+
+pub mod messages {
+    use bevy::prelude::*;
+
+    /// Fired when a player interacts with an [`Interactable`](super::Interactable).
+    /// Targets the interactable.
+    #[derive(Event, Copy, Clone, Eq, PartialEq, Debug)]
+    pub struct Interacted {
+        pub interactor: Entity,
+    }
+}
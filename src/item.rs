@@ -1,5 +1,13 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
 use derive_more::*;
-use crate::equipment::{Equippable, Outfit};
+use tiled_parser::PropertyValue;
+use crate::area::AreaLocal;
+use crate::common::CommonAssets;
+use crate::equipment::{Equippable, Hat, Outfit, Weapon};
+use crate::water::{Buoyancy, WaterContact};
+
+const ITEM_SIZE: f32 = 3.0;
 
 
 #[derive(From, Clone, PartialEq, Debug)]
@@ -7,9 +15,135 @@ pub enum Item {
     Equippable(Equippable),
 }
 
+impl Item {
+    /// Whether the item floats or sinks when dropped in deep water.
+    pub fn buoyancy(&self) -> Buoyancy {
+        match self {
+            Self::Equippable(Equippable::Weapon(_)) => Buoyancy::Sinks,
+            Self::Equippable(_) => Buoyancy::Floats,
+        }
+    }
+
+    pub fn parse(item: &str) -> Option<Self> {
+        let equippable: Equippable = match item {
+            "pointy_hat"    => Hat::Pointy.into(),
+            "casual_1"      => Outfit::Casual1.into(),
+            "casual_2"      => Outfit::Casual2.into(),
+            "casual_3"      => Outfit::Casual3.into(),
+            "casual_4"      => Outfit::Casual4.into(),
+            "casual_5"      => Outfit::Casual5.into(),
+            "wooden_axe"    => Weapon::WoodenAxe.into(),
+            _               => return None,
+        };
+        Some(equippable.into())
+    }
+}
 
 impl From<Outfit> for Item {
     fn from(outfit: Outfit) -> Self {
         Self::Equippable(Equippable::from(outfit))
     }
-}
\ No newline at end of file
+}
+
+/// An [`Item`] lying in the world.
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct DroppedItem(pub Item);
+
+/// Components of an item lying in the world.
+/// Floats or sinks according to the item if it lands in deep water.
+#[derive(Bundle, Debug)]
+pub struct ItemBundle {
+    pub item: DroppedItem,
+    pub buoyancy: Buoyancy,
+    pub water_contact: WaterContact,
+    pub spatial: SpatialBundle,
+}
+
+impl ItemBundle {
+    pub fn new(item: Item, position: Vec3) -> Self {
+        Self {
+            buoyancy: item.buoyancy(),
+            item: DroppedItem(item),
+            water_contact: WaterContact::default(),
+            spatial: SpatialBundle::from_transform(Transform::from_translation(position)),
+        }
+    }
+}
+
+/// Drops an item in the world.
+pub fn drop_item(
+    commands: &mut Commands,
+    item: Item,
+    position: Vec3,
+    common_assets: &CommonAssets,
+) -> Entity {
+    let mut bundle = ItemBundle::new(item, position);
+    bundle.spatial.transform.scale = Vec3::splat(ITEM_SIZE);
+    commands.spawn((
+        Name::new("item"),
+        bundle,
+        common_assets.meshes.sphere.clone(),
+        common_assets.materials.white.clone(),
+        AreaLocal { size: Vec2::splat(ITEM_SIZE) },
+    )).id()
+}
+
+/// Spawns the item named by an object's 'item' property.
+pub fn spawn_item(
+    commands: &mut Commands,
+    position: Vec3,
+    properties: &HashMap<String, PropertyValue>,
+    common_assets: &CommonAssets,
+) {
+    let item = match properties.get("item") {
+        Some(PropertyValue::String(item)) => Item::parse(item).unwrap_or_else(|| panic!("Unknown item '{item}'")),
+        Some(_) => panic!("Property 'item' not a string"),
+        None => panic!("Property 'item' missing"),
+    };
+    drop_item(commands, item, position, common_assets);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use bevy::ecs::system::RunSystemOnce;
+    use crate::water::{apply_buoyancy, WaterDepth};
+    use super::*;
+
+    /// Spawns an item in deep water whose surface is at y = 0.
+    fn spawn_in_deep_water(world: &mut World, item: impl Into<Item>, y: f32) -> Entity {
+        let mut bundle = ItemBundle::new(item.into(), Vec3::new(0.0, y, 0.0));
+        bundle.water_contact = WaterContact { depth: Some(WaterDepth::Deep), surface: 0.0 };
+        world.spawn(bundle).id()
+    }
+
+    fn tick(world: &mut World) {
+        world.resource_mut::<Time>().advance_by(Duration::from_millis(100));
+        world.run_system_once(apply_buoyancy);
+    }
+
+    #[test]
+    fn floating_item_rises_to_surface() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        let outfit = spawn_in_deep_water(&mut world, Outfit::Casual1, -8.0);
+        assert_eq!(Some(&Buoyancy::Floats), world.get::<Buoyancy>(outfit));
+        tick(&mut world);
+        let y = world.get::<Transform>(outfit).unwrap().translation.y;
+        assert!(y.abs() <= 1.0, "Expected item at the surface, got y = {y}");
+    }
+
+    #[test]
+    fn sinking_item_goes_down() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        let axe = spawn_in_deep_water(&mut world, Equippable::from(Weapon::WoodenAxe), -1.0);
+        assert_eq!(Some(&Buoyancy::Sinks), world.get::<Buoyancy>(axe));
+        tick(&mut world);
+        let y1 = world.get::<Transform>(axe).unwrap().translation.y;
+        tick(&mut world);
+        let y2 = world.get::<Transform>(axe).unwrap().translation.y;
+        assert!(y1 < -1.0 && y2 < y1, "Expected item to sink, got y = {y1} then {y2}");
+    }
+}
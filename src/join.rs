@@ -0,0 +1,140 @@
+//! Join screen where local players pick the devices they play with.
+
+use bevy::prelude::*;
+use crate::input::InputDevice;
+use crate::messages::SpawnPlayer;
+use crate::ui::{c_frame, c_text, create_node, Dsl, NodeW, SlicedImageW, TextW};
+use crate::ScreenStates;
+
+pub const MAX_PLAYERS: usize = 4;
+const FONT_SIZE: f32 = 14.0;
+/// Distance between players when they spawn.
+const PLAYER_SPACING: f32 = 24.0;
+
+
+/// Devices of the players that joined, in the order they joined.
+#[derive(Resource, Clone, PartialEq, Debug)]
+pub struct Lobby {
+    pub devices: Vec<InputDevice>,
+    /// Where the first player spawns once the game starts.
+    /// Every other player spawns to the right of the one before it.
+    pub spawn_position: Vec3,
+}
+
+impl Lobby {
+    pub fn new(spawn_position: Vec3) -> Self {
+        Self { devices: vec![], spawn_position }
+    }
+}
+
+/// Root UI entity of the join screen.
+#[derive(Component, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct LobbyScreen;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum LobbyInput {
+    Join,
+    Leave,
+    Start,
+}
+
+/// Joins / leaves players as devices press join / leave.
+/// Starts the game when a player presses start.
+pub fn join_players(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    mut lobby: ResMut<Lobby>,
+    mut next_screen: ResMut<NextState<ScreenStates>>,
+) {
+    let mut inputs: Vec<(InputDevice, LobbyInput)> = vec![];
+    if keyboard.just_released(KeyCode::Space) { inputs.push((InputDevice::Keyboard, LobbyInput::Join)) }
+    if keyboard.any_just_pressed([KeyCode::Escape, KeyCode::Backspace]) { inputs.push((InputDevice::Keyboard, LobbyInput::Leave)) }
+    if keyboard.just_released(KeyCode::Enter) { inputs.push((InputDevice::Keyboard, LobbyInput::Start)) }
+    for gamepad in gamepads.iter() {
+        let device = InputDevice::Gamepad(gamepad);
+        let button = |button_type| GamepadButton::new(gamepad, button_type);
+        if gamepad_buttons.just_released(button(GamepadButtonType::South)) { inputs.push((device, LobbyInput::Join)) }
+        if gamepad_buttons.just_pressed(button(GamepadButtonType::East)) { inputs.push((device, LobbyInput::Leave)) }
+        if gamepad_buttons.just_released(button(GamepadButtonType::Start)) { inputs.push((device, LobbyInput::Start)) }
+    }
+
+    // Join and start are handled on release, so that their presses do not carry over to the players spawned.
+    // IE: join is also confirm.
+    for (device, input) in inputs {
+        let joined = lobby.devices.contains(&device);
+        match (input, joined) {
+            (LobbyInput::Join, false) if lobby.devices.len() < MAX_PLAYERS => lobby.devices.push(device),
+            (LobbyInput::Leave, true) => lobby.devices.retain(|joined_device| *joined_device != device),
+            (LobbyInput::Start, true) => next_screen.set(ScreenStates::Overworld),
+            _ => {}
+        }
+    }
+}
+
+/// Rebuilds the join screen when players join or leave.
+pub fn render_lobby(
+    lobby: Res<Lobby>,
+    screens: Query<Entity, With<LobbyScreen>>,
+    assets: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    let screen = match screens.get_single() {
+        Ok(screen) => {
+            commands.entity(screen).despawn_descendants();
+            screen
+        },
+        Err(_) => commands.spawn((Name::new("lobby"), LobbyScreen, create_node(c_lobby_root))).id(),
+    };
+    let d = &mut Dsl::new(&mut commands, screen);
+    let font = assets.load::<Font>("fonts/Retro Gaming.ttf");
+    let frame = assets.load::<Image>("ui/UI_Paper_Frame_01_Standard.png");
+
+    SlicedImageW::new(&frame).class(c_frame).class(c_lobby_frame).begin(d);
+        NodeW::cls(c_column).begin(d);
+            for player_idx in 0..MAX_PLAYERS {
+                let text = match lobby.devices.get(player_idx) {
+                    Some(device) => format!("Player {}: {device}", player_idx + 1),
+                    None => format!("Player {}: Press Space / A to join", player_idx + 1),
+                };
+                TextW::new(text).class(c_text).font(&font).font_size(FONT_SIZE).spawn(d);
+            }
+            if !lobby.devices.is_empty() {
+                TextW::new("Press Enter / Start to begin").class(c_text).font(&font).font_size(FONT_SIZE).spawn(d);
+            }
+        NodeW::end(d);
+    SlicedImageW::end(d);
+}
+
+pub fn despawn_lobby_screen(screens: Query<Entity, With<LobbyScreen>>, mut commands: Commands) {
+    for screen in &screens {
+        commands.entity(screen).despawn_recursive();
+    }
+}
+
+/// Spawns a player for each device in the lobby.
+pub fn spawn_lobby_players(lobby: Res<Lobby>, mut commands: Commands) {
+    for (player_idx, device) in lobby.devices.iter().copied().enumerate() {
+        let position = lobby.spawn_position + Vec3::X * PLAYER_SPACING * player_idx as f32;
+        commands.trigger(SpawnPlayer { position, device });
+    }
+}
+
+
+fn c_lobby_root(node: &mut NodeBundle) {
+    node.style.position_type = PositionType::Absolute;
+    node.style.width = Val::Percent(100.0);
+    node.style.height = Val::Percent(100.0);
+    node.style.justify_content = JustifyContent::Center;
+    node.style.align_items = AlignItems::Center;
+}
+
+fn c_lobby_frame(image: &mut SlicedImageW) {
+    let style = &mut image.image.style;
+    style.padding = UiRect::all(Val::Px(16.0));
+}
+
+fn c_column(node: &mut NodeW) {
+    node.style.flex_direction = FlexDirection::Column;
+    node.style.row_gap = Val::Px(4.0);
+}
@@ -9,12 +9,24 @@ mod daynight;
 mod player;
 mod mobs;
 mod objects;
+mod combat;
 mod common;
 mod input;
+mod interact;
+mod join;
 mod item;
 mod equipment;
 mod debug;
+mod dialogue;
+mod flags;
+mod footstep;
+mod trigger;
+mod save;
+mod script;
+mod state_machine;
+mod stats;
 mod ui;
+mod water;
 
 use bevy::prelude::*;
 use bevy::pbr::{DirectionalLightShadowMap, PbrProjectionPlugin};
@@ -25,11 +37,25 @@ use bevy::utils::HashMap;
 use bevy_mod_sprite3d::Sprite3dPlugin;
 
 use camera::DualProjection;
-pub use action::ActionKind;
+pub use action::{ActionId, ActionQueue, ActionRegistry, AppActionExt, Exclusivity, OnFinish};
+pub use action::composite::{Parallel, Race, Repeat};
+pub use action::tween::{Ease, Lens, TweenValue};
+pub use act::StartEnvExt;
+pub use flags::{Flag, Flags};
+pub use input::InputDevice;
+pub use item::Item;
+pub use join::Lobby;
+pub use stats::{BaseStats, Modifier, ModifierKind, Stat, Stats};
+pub use state_machine::{MachineState, StateAnimation, StateAnimations, StateMachine};
+pub use map::{NavCell, NavGrid, Navigation, Surface};
+pub use player::{CharacterController, CompassDirection, Facing, FacingMode, Impulse};
+pub use water::{Buoyancy, Water, WaterContact, WaterDepth};
 use daynight::GameTime;
 use debug::DebugStates;
 use equipment::Equipment;
 use round::RoundUnitSize;
+use mobs::FireflyBehavior;
+use player::PlayerBehavior;
 use ui::messages::MenuEvent;
 use ui::EquipmentMenu;
 
@@ -57,8 +83,12 @@ impl Plugin for GamePlugin {
         // States and resources
         app.init_state::<ScreenStates>();
         app.init_state::<debug::DebugStates>();
+        app.init_state::<PauseStates>();
         app.init_resource::<EntityIndex>();
         app.init_resource::<RoundUnitSize>();
+        app.init_resource::<action::ActionSchedule>();
+        app.init_resource::<action::ActionRegistry>();
+        app.init_resource::<flags::Flags>();
 
         // Observers
         app.observe(action::run_action);
@@ -67,9 +97,21 @@ impl Plugin for GamePlugin {
         app.observe(map::despawn_map);
         app.observe(map::spawn_entity);
         app.observe(player::spawn_player);
+        app.observe(player::forget_player);
         app.observe(area::init_area);
         app.observe(ui::toggle_equipment_menu);
         app.observe(ui::handle_menu_events);
+        app.observe(stats::apply_damage);
+        app.observe(stats::apply_heal);
+        app.observe(stats::handle_death);
+        app.observe(player::enter_attacking);
+        app.observe(player::enter_dodging);
+        app.observe(mobs::enter_firefly_behavior);
+        app.observe(dialogue::start_dialogue);
+        app.observe(dialogue::end_dialogue);
+        app.observe(save::save_game);
+        app.observe(save::load_game);
+        app.observe(footstep::spawn_footstep_dust);
 
         // Daynight
         app.init_resource::<daynight::GameTime>();
@@ -82,6 +124,17 @@ impl Plugin for GamePlugin {
         app.init_asset_loader::<map::MapLoader>();
         app.init_asset_loader::<map::TilesetLoader>();
         app.init_asset_loader::<map::AreaLoader>();
+
+        // Actions
+        app.register_action("cutscene", action::common::cutscene, Exclusivity::Single);
+
+        // Scripts
+        app.init_asset::<script::Script>();
+        app.init_asset_loader::<script::ScriptLoader>();
+
+        // Dialogue
+        app.init_asset::<dialogue::Dialogue>();
+        app.init_asset_loader::<dialogue::DialogueLoader>();
         
         // Common
         app.init_resource::<common::CommonAssets>();
@@ -103,9 +156,13 @@ impl Plugin for GamePlugin {
                 input::map_gamepads,
                 ui::render_equipment_menu
                     .run_if(resource_exists_and_changed::<EquipmentMenu>),
-                player::assign_gamepad_to_player,
-                action::run_action_queues,
+                player::assign_gamepads_to_players,
+                action::run_action_queues
+                    .after(input::map_keyboard)
+                    .after(input::map_gamepads)
+                    .run_if(resource_equals(action::ActionSchedule::Update)),
                 map::process_loaded_maps,
+                map::check_tile_sizes.after(map::process_loaded_maps),
                 area::stream_current_area,
                 area::despawn_area_locals,
                 daynight::update_game_time,
@@ -116,18 +173,47 @@ impl Plugin for GamePlugin {
             /////////////// Flush ///////////////
             apply_deferred.in_set(GameSystems::Flush),
 
+            /////////////// PreLogic ///////////////
+            (
+                (stats::update_stats, stats::sync_top_speed).chain(),
+                water::update_water_contacts,
+                footstep::update_surfaces,
+                objects::apply_force_zones,
+            ).in_set(GameSystems::PreLogic),
+
             /////////////// Logic ///////////////
             (
                 player::update_players,
+                state_machine::apply_transitions::<PlayerBehavior>.after(player::update_players),
+                state_machine::update_state_animations::<PlayerBehavior>
+                    .after(state_machine::apply_transitions::<PlayerBehavior>),
                 player::update_character_controllers.after(player::update_players),
-                player::update_player_animations.after(player::update_players),
+                interact::select_interaction_targets.after(state_machine::apply_transitions::<PlayerBehavior>),
+                interact::interact.after(interact::select_interaction_targets),
+                interact::render_interact_prompt.after(interact::interact),
                 mobs::update_fireflies,
+                water::apply_buoyancy,
+                state_machine::apply_transitions::<FireflyBehavior>.after(mobs::update_fireflies),
                 ui::handle_interactions::<MenuEvent>,
+                (
+                    join::join_players,
+                    join::render_lobby
+                        .run_if(resource_changed::<Lobby>),
+                ).chain().run_if(in_state(ScreenStates::Title).and_then(resource_exists::<Lobby>)),
+                (
+                    dialogue::update_dialogue,
+                    dialogue::render_dialogue
+                        .run_if(resource_exists_and_changed::<dialogue::ActiveDialogue>),
+                    dialogue::type_dialogue_text,
+                ).chain().run_if(resource_exists::<dialogue::ActiveDialogue>),
                 debug::toggle_debug,
                 camera::update_flycam,
                 (
                     camera::toggle_projection,
                     camera::toggle_flycam,
+                    debug::render_action_debugger,
+                    debug::quick_save,
+                    combat::draw_hitboxes,
                 ).run_if(in_state(DebugStates::Enabled)),
             ).in_set(GameSystems::Logic),
 
@@ -137,10 +223,18 @@ impl Plugin for GamePlugin {
                 equipment::spawn_equipment_entities,
                 animation::update_animations.after(equipment::spawn_equipment_entities),
                 animation::sync_animations.after(animation::update_animations),
-                player::sync_players,
+                trigger::update_trigger_zones,
+                combat::resolve_hitboxes,
+                water::update_splashes,
+                footstep::emit_footsteps.after(animation::update_animations),
+                footstep::update_dust,
             ).in_set(GameSystems::PostLogic),
         ));
 
+        app.add_systems(FixedUpdate, action::run_action_queues
+            .run_if(resource_equals(action::ActionSchedule::FixedUpdate))
+        );
+
         app.add_systems(PostUpdate, (
             round::round_translations
                 .before(TransformSystem::TransformPropagate),
@@ -151,6 +245,10 @@ impl Plugin for GamePlugin {
         ));
 
         app.add_systems(OnEnter(DebugStates::Disabled), camera::handle_disable_debug);
+        app.add_systems(OnExit(ScreenStates::Title), join::despawn_lobby_screen);
+        app.add_systems(OnEnter(ScreenStates::Overworld), join::spawn_lobby_players
+            .run_if(resource_exists::<Lobby>)
+        );
     }
 }
 
@@ -181,10 +279,20 @@ pub enum ScreenStates {
     Overworld,
 }
 
+/// Whether or not gameplay is paused.
+/// Pausable [`ActionQueue`](action::ActionQueue)s stop running while paused.
+#[derive(States, Clone, Eq, PartialEq, Hash, Default, Debug)]
+pub enum PauseStates {
+    #[default]
+    Running,
+    Paused,
+}
+
 /// An index that keeps track of particular entities.
 #[derive(Resource, Default, Debug)]
 pub struct EntityIndex {
-    pub player: Option<Entity>,
+    /// Players in the order they joined.
+    pub players: Vec<Entity>,
     pub maps: HashMap<String, Entity>,
 }
 
@@ -198,4 +306,17 @@ pub mod messages {
     pub use crate::action::messages::RunAction;
     pub use crate::action::messages::QuitAction;
     pub use crate::ui::messages::ToggleEquipmentMenu;
+    pub use crate::trigger::messages::ZoneEntered;
+    pub use crate::trigger::messages::ZoneExited;
+    pub use crate::dialogue::messages::StartDialogue;
+    pub use crate::dialogue::messages::EndDialogue;
+    pub use crate::save::messages::SaveGame;
+    pub use crate::save::messages::LoadGame;
+    pub use crate::combat::messages::Damaged;
+    pub use crate::stats::messages::Heal;
+    pub use crate::stats::messages::Died;
+    pub use crate::interact::messages::Interacted;
+    pub use crate::footstep::messages::Footstep;
+    pub use crate::state_machine::messages::EnterState;
+    pub use crate::state_machine::messages::ExitState;
 }
\ No newline at end of file
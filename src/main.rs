@@ -1,5 +1,5 @@
 use bevy::prelude::*;
-use rpg_tournament::GamePlugin;
+use rpg_tournament::{GamePlugin, Lobby};
 use rpg_tournament::messages::*;
 
 fn main() {
@@ -12,5 +12,5 @@ fn main() {
 
 fn startup(mut commands: Commands) {
     commands.trigger(InitArea { name: "Overworld", file: "worlds/overworld.world" });
-    commands.trigger(SpawnPlayer { position: Vec3::new(200.0, 0.0, -400.0) });
+    commands.insert_resource(Lobby::new(Vec3::new(200.0, 0.0, -400.0)));
 }
\ No newline at end of file
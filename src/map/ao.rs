@@ -0,0 +1,62 @@
+use bevy::math::I16Vec3;
+use bevy::utils::HashMap;
+use tiled_parser as tp;
+use super::{parse_float, TH};
+
+
+/// Heights of the tiles in a group layer.
+/// Used for baking ambient occlusion into the vertices of its meshes.
+#[derive(Default, Debug)]
+pub struct HeightMap {
+    heights: HashMap<(i16, i16), i16>,  // (column, projected row) -> highest point of tile
+}
+
+impl HeightMap {
+
+    /// Records the height of a tile.
+    /// Row is the tile's projected row, which increases northwards.
+    pub fn insert(&mut self, column: i16, row: i16, height: i16) {
+        let entry = self.heights.entry((column, row)).or_insert(height);
+        *entry = (*entry).max(height);
+    }
+
+    /// Fraction of the tiles surrounding a vertex that rise above it.
+    /// 0.0 = unoccluded, 1.0 = fully occluded.
+    pub fn occlusion(&self, pos: I16Vec3) -> f32 {
+        let row = (pos.y - pos.z).div_euclid(TH);
+        let neighbors = [
+            (pos.x - 1, row - 1),
+            (pos.x,     row - 1),
+            (pos.x - 1, row),
+            (pos.x,     row),
+        ];
+        let occluders = neighbors.iter()
+            .filter_map(|coords| self.heights.get(coords))
+            .filter(|height| **height > pos.y)
+            .count();
+        occluders as f32 / neighbors.len() as f32
+    }
+}
+
+/// Ambient occlusion settings of a mesh.
+#[derive(Copy, Clone, Debug)]
+pub struct AmbientOcclusion<'a> {
+    pub heights: &'a HeightMap,
+    /// How dark fully occluded vertices become, between 0.0 and 1.0.
+    pub strength: f32,
+}
+
+impl<'a> AmbientOcclusion<'a> {
+    /// Vertex color of a vertex at the position specified.
+    pub fn color_of(&self, pos: I16Vec3) -> [f32; 4] {
+        let brightness = 1.0 - self.strength * self.heights.occlusion(pos);
+        [brightness, brightness, brightness, 1.0]
+    }
+}
+
+/// Parses the 'ao' property, which is the strength of ambient occlusion.
+pub fn parse_ao_strength(props: &tp::Properties) -> Option<f32> {
+    props.iter()
+        .find(|(prop_name, _)| *prop_name == "ao")
+        .map(|(prop_name, prop_value)| parse_float(prop_name, prop_value, 0.0).clamp(0.0, 1.0))
+}
@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use bevy::utils::HashMap;
+use bevy_mod_sprite3d::Sprite3d;
+use tiled_parser::PropertyValue;
+use tiled_parser as tp;
+use super::{parse_float, Map, Tileset};
+
+
+/// A static sprite placed on a map from a Tiled tile object.
+/// IE: Trees, rocks, signs etc.
+#[derive(Component, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct Decoration;
+
+/// Solid region on the ground that characters cannot walk through.
+/// Centered on the entity's translation, along the xz plane.
+#[derive(Component, Copy, Clone, PartialEq, Default, Debug)]
+pub struct Footprint {
+    pub size: Vec2,
+}
+
+impl Footprint {
+
+    /// Pushes a point out of the footprint along the axis of least penetration.
+    /// Returns [`None`] if the point is not inside the footprint.
+    pub fn push_out(&self, footprint_pos: Vec3, point: Vec3) -> Option<Vec3> {
+        let hsize = self.size / 2.0;
+        let diff = Vec2::new(point.x - footprint_pos.x, point.z - footprint_pos.z);
+        let pen = hsize - diff.abs();
+        if pen.x <= 0.0 || pen.y <= 0.0 { return None };
+        let mut result = point;
+        if pen.x < pen.y {
+            result.x += pen.x * diff.x.signum();
+        }
+        else {
+            result.z += pen.y * diff.y.signum();
+        }
+        Some(result)
+    }
+}
+
+/// Spawns a tile object as a [`Decoration`] sprite that is a child of the map.
+/// Sprite is anchored at its base, which is the bottom of the object in Tiled.
+/// Does nothing if the object does not reference a tile.
+pub(super) fn spawn_decoration(
+    commands: &mut Commands,
+    object: &tp::Object,
+    map: &Map,
+    map_entity: Entity,
+    map_height_px: f32,
+    tileset_assets: &Assets<Tileset>,
+    material_assets: &mut Assets<StandardMaterial>,
+    materials: &mut HashMap<usize, Handle<StandardMaterial>>,
+) {
+    let Some(tile_gid) = object.gid() else { return };
+    let Some((tileset_idx, tile_id)) = map.map.tile_location_of(tile_gid) else {
+        bevy::log::warn!("Tile object '{}' references an unknown tile", object.name());
+        return;
+    };
+    let tileset_entry = &map.tileset_entries[tileset_idx];
+    let tileset = tileset_assets.get(&tileset_entry.tileset).expect("A tileset was not fully loaded");
+
+    // Parses properties
+    let tile_height = map.map.tile_height() as f32;
+    let mut lift = 0.0;
+    let mut collision = false;
+    let mut collision_depth = tile_height / 2.0;
+    for (prop_name, prop_value) in object.properties() {
+        match (prop_name, prop_value) {
+            ("lift", value) => lift = parse_float("lift", value, 0.0),
+            ("collision", PropertyValue::Bool(value)) => collision = *value,
+            ("collision_depth", value) => collision_depth = parse_float("collision_depth", value, tile_height / 2.0),
+            ("collision", _) => panic!("Property 'collision' not a bool"),
+            _ => {}
+        }
+    }
+
+    // Region of the tile in the tileset's image
+    let columns = tileset.tileset.columns();
+    let tile_size = Vec2::new(tileset.tileset.tile_width() as f32, tileset.tileset.tile_height() as f32);
+    let tile_pos = Vec2::new((tile_id % columns) as f32, (tile_id / columns) as f32) * tile_size;
+    let rect = Rect { min: tile_pos, max: tile_pos + tile_size };
+
+    // Tile objects are positioned by their bottom-left corner.
+    // Translation is relative to the map.
+    let translation = Vec3::new(
+        object.x() + object.width() / 2.0,
+        lift * tile_height,
+        object.y() + lift * tile_height - map_height_px,
+    );
+    let material = materials
+        .entry(tileset_idx)
+        .or_insert_with(|| material_assets.add(StandardMaterial {
+            base_color: tileset.base_color,
+            base_color_texture: Some(tileset.base_color_texture.clone()),
+            emissive: tileset.emissive,
+            emissive_texture: tileset.emissive_texture.clone(),
+            normal_map_texture: tileset.normal_texture.clone(),
+            perceptual_roughness: 1.0,
+            reflectance: 0.0,
+            alpha_mode: AlphaMode::Mask(0.5),
+            cull_mode: None,
+            ..default()
+        }))
+        .clone();
+    commands.entity(map_entity).with_children(|b| {
+        let mut decoration = b.spawn((
+            Name::new(format!("decoration-{}", object.id())),
+            Decoration,
+            Sprite3d {
+                rect: Some(rect),
+                custom_size: Some(Vec2::new(object.width(), object.height())),
+                anchor: Anchor::BottomCenter,
+                ..default()
+            },
+            material,
+            SpatialBundle::from_transform(Transform::from_translation(translation)),
+        ));
+        if collision {
+            decoration.insert(Footprint { size: Vec2::new(object.width(), collision_depth) });
+        }
+    });
+}
@@ -1,6 +1,10 @@
 use bevy::prelude::*;
+use bevy::utils::HashMap;
+use tiled_parser::PropertyValue;
 use crate::common::CommonAssets;
 use crate::daynight::GameTime;
+use crate::flags::Flags;
+use crate::objects::ForceKind;
 
 
 pub fn spawn_entity(
@@ -8,35 +12,70 @@ pub fn spawn_entity(
     common_assets: Res<CommonAssets>,
     assets: Res<AssetServer>,
     game_time: Res<GameTime>,
+    flags: Res<Flags>,
     mut commands: Commands,
 ) {
     let message = trigger.event();
+    if !flags_allow_spawn(&message.properties, &flags) { return };
     match message.entity_type {
-        EntityType::Firefly => crate::mobs::spawn_firefly(&mut commands, message.position, &common_assets, game_time.time_fraction()),
-        EntityType::Water   => crate::objects::spawn_water(&mut commands, message.position, message.size, &common_assets, &assets),
+        EntityType::Firefly      => crate::mobs::spawn_firefly(&mut commands, message.position, &common_assets, game_time.time_fraction()),
+        EntityType::Water        => crate::objects::spawn_water(&mut commands, message.position, message.size, &common_assets, &assets),
+        EntityType::Trigger      => crate::trigger::spawn_trigger_zone(&mut commands, message.position, message.size, &message.properties),
+        EntityType::Interactable => crate::interact::spawn_interactable(&mut commands, message.position, message.size, &message.properties),
+        EntityType::Wind         => crate::objects::spawn_force_zone(&mut commands, message.position, message.size, ForceKind::Wind, &message.properties),
+        EntityType::Conveyor     => crate::objects::spawn_force_zone(&mut commands, message.position, message.size, ForceKind::Conveyor, &message.properties),
+        EntityType::Item         => crate::item::spawn_item(&mut commands, message.position, &message.properties, &common_assets),
     }
 }
 
+/// Checks the 'if_flag' and 'unless_flag' properties of an object.
+/// 'if_flag' requires the flag to be set, and 'unless_flag' requires it to be unset.
+fn flags_allow_spawn(properties: &HashMap<String, PropertyValue>, flags: &Flags) -> bool {
+    for (prop_name, prop_value) in properties {
+        match (prop_name.as_str(), prop_value) {
+            ("if_flag", PropertyValue::String(flag)) if !flags.is_set(flag) => return false,
+            ("unless_flag", PropertyValue::String(flag)) if flags.is_set(flag) => return false,
+            ("if_flag" | "unless_flag", PropertyValue::String(_)) => {}
+            ("if_flag", _) => panic!("Property 'if_flag' not a string"),
+            ("unless_flag", _) => panic!("Property 'unless_flag' not a string"),
+            _ => {}
+        }
+    }
+    true
+}
 
-#[derive(Event, Copy, Clone, PartialEq, Debug)]
+
+#[derive(Event, Clone, Debug)]
 pub struct SpawnEntity {
     pub entity_type: EntityType,
     pub position: Vec3,
     pub size: Vec3,
+    /// Properties of the object the entity was spawned from.
+    pub properties: HashMap<String, PropertyValue>,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum EntityType {
     Firefly,
     Water,
+    Trigger,
+    Interactable,
+    Wind,
+    Conveyor,
+    Item,
 }
 
 impl EntityType {
     pub fn parse(entity_type: &str) -> Option<Self> {
         match entity_type {
-            "firefly"   => Some(Self::Firefly),
-            "water"     => Some(Self::Water),
-            _           => None,
+            "firefly"      => Some(Self::Firefly),
+            "water"        => Some(Self::Water),
+            "trigger"      => Some(Self::Trigger),
+            "interactable" => Some(Self::Interactable),
+            "wind"         => Some(Self::Wind),
+            "conveyor"     => Some(Self::Conveyor),
+            "item"         => Some(Self::Item),
+            _              => None,
         }
     }
-}
\ No newline at end of file
+}
@@ -5,6 +5,7 @@ use bevy::render::mesh::{Indices, PrimitiveTopology};
 use bevy::render::render_asset::RenderAssetUsages;
 use bevy::utils::HashMap;
 
+use super::ao::AmbientOcclusion;
 use super::TH;
 
 //pub type CollisionMesh = HashMesh<CollisionVertex>;
@@ -28,6 +29,10 @@ impl<V> HashMesh<V> {
         }
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
     pub fn finish(self) -> (Vec<V>, Vec<u32>) {
         (
             self.vertices,
@@ -70,7 +75,8 @@ pub fn create_bevy_mesh(
     material_width: f32,
     material_height: f32,
     tile_width: f32,
-    tile_height: f32
+    tile_height: f32,
+    ao: Option<AmbientOcclusion>,
 ) -> Mesh {
     const EPS: f32 = 0.001;
     let (gmesh_verts, gmesh_indices) = gmesh.finish();
@@ -95,11 +101,17 @@ pub fn create_bevy_mesh(
             gvert.uv.y as f32 * (1.0 / material_height),
         ])
         .collect();
-    let mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::RENDER_WORLD)
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::RENDER_WORLD)
         .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
         .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
         .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
         .with_inserted_indices(Indices::U32(gmesh_indices));
+    if let Some(ao) = ao {
+        let colors: Vec<[f32; 4]> = gmesh_verts.iter()
+            .map(|gvert| ao.color_of(gvert.pos))
+            .collect();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    }
     mesh
 }
 
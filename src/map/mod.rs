@@ -1,13 +1,21 @@
+mod ao;
+mod decoration;
 mod entities;
 mod loader;
 mod mesh;
+mod nav;
+#[cfg(test)]
+mod tests;
 
+pub use decoration::*;
 pub use entities::*;
 pub use loader::*;
+pub use nav::*;
 
 use bevy::math::I16Vec2;
 use bevy::math::I16Vec3;
 use bevy::utils::HashMap;
+use ao::{parse_ao_strength, AmbientOcclusion, HeightMap};
 use bitflags::bitflags;
 use mesh::create_bevy_mesh;
 use mesh::GraphicsMesh;
@@ -22,6 +30,7 @@ use crate::EntityIndex;
 
 pub const TH: i16 = 2;  // Tile height
 pub const THH: i16 = 1; // Half tile height
+pub const CHUNK_SIZE: i16 = 16; // Width and height of a mesh chunk in tiles
 
 
 /// Spawns a [`Map`] entity.
@@ -103,6 +112,8 @@ fn process_map(
 ) {
     let mut vert_offset: u16 = 0;
     let map = map_assets.get(map_handle).unwrap();
+    let tile_size = Vec2::new(map.map.tile_width() as f32, map.map.tile_height() as f32);
+    let mut nav_grid = NavGrid::new(map_position, map.map.width(), map.map.height(), tile_size);
     for layer in map.map.layers() {
         match layer.kind() {
             tp::LayerKind::GroupLayer(group_layer) => process_group_layer(
@@ -115,19 +126,25 @@ fn process_map(
                 tileset_assets,
                 material_assets,
                 mesh_assets,
+                &mut nav_grid,
                 &mut vert_offset,
             ),
             tp::LayerKind::ObjectGroupLayer(object_layer) => process_object_layer(
                 commands,
                 object_layer,
+                map,
+                map_entity,
                 map.map.tile_height() as f32,
                 map.map.tile_height() as f32 * map.map.height() as f32,
                 map_position,
+                tileset_assets,
+                material_assets,
             ),
             tp::LayerKind::TileLayer(_) => panic!("Unexpected tile layer"),
             tp::LayerKind::ImageLayer(_) => panic!("Unexpected image layer"),
         }
     }
+    commands.entity(map_entity).insert(nav_grid);
     log::info!("Finished map");
 }
 
@@ -141,6 +158,7 @@ fn process_group_layer(
     tileset_assets: &Assets<Tileset>,
     material_assets: &mut Assets<StandardMaterial>,
     mesh_assets: &mut Assets<Mesh>,
+    nav_grid: &mut NavGrid,
     vert_offset: &mut u16,
 ) {
 
@@ -151,10 +169,12 @@ fn process_group_layer(
         map,
         &tileset_assets,
     );
+    nav_grid.insert_group_layer(&regular_layers, &group_meta);
 
-    // Forms graphics meshes, parallel with the map's tileset entries.
-    let mut cliff_mesh = GraphicsMesh::new();
-    let mut gmeshes: Vec<GraphicsMesh> = init_graphics_meshes(map.tileset_entries.len());
+    // Forms graphics meshes of each chunk, parallel with the map's tileset entries.
+    // Records tile heights for ambient occlusion.
+    let mut chunks: HashMap<(i16, i16), MeshChunk> = HashMap::new();
+    let mut heights = HeightMap::default();
     for layer in regular_layers {
         let region = layer.region;
         let (min_x, max_x) = (region.x, region.x + region.width as i32);
@@ -176,7 +196,10 @@ fn process_group_layer(
                 let tile_y = tile_y as i16;
                 let tile_coords = (tile_x, tile_y);
 
-                // Gets tile and tile meta
+                // Gets chunk, tile and tile meta
+                let chunk = chunks
+                    .entry((tile_x.div_euclid(CHUNK_SIZE), tile_y.div_euclid(CHUNK_SIZE)))
+                    .or_insert_with(|| MeshChunk::new(map.tileset_entries.len()));
                 let tile = layer.tiles.get(&tile_coords);
                 let tile_geom = group_meta.graphics_geoms.get(&tile_coords).copied().unwrap_or_default();
                 let tile_quad_info = tile_geom.shape.quad_info();
@@ -198,8 +221,13 @@ fn process_group_layer(
                     };
 
                     // Pushes quad to relevant mesh
-                    let gmesh = &mut gmeshes[tile.tileset_idx];
+                    let gmesh = &mut chunk.gmeshes[tile.tileset_idx];
                     gmesh.push_quad(tile_vertices);
+
+                    // Records height of tile
+                    let tile_row = (gstrip.left.y - gstrip.left.z) / TH;
+                    let tile_top = gstrip.left.y.max(gstrip.right.y).max(gstrip_next.left.y).max(gstrip_next.right.y);
+                    heights.insert(tile_x, tile_row, tile_top);
                 }
 
                 // Pushes northern cliff vertices
@@ -212,7 +240,7 @@ fn process_group_layer(
                     let cliff_points = [point_a, point_b, point_b.with_y(lift), point_a.with_y(lift)];
                     let cliff_uvs = [I16Vec2::ZERO; 4];
                     let cliff_verts = GraphicsVertex::quad(cliff_points, cliff_uvs, 0);
-                    cliff_mesh.push_quad(cliff_verts);
+                    chunk.cliff_mesh.push_quad(cliff_verts);
                 }
 
                 // Pushes eastern cliff vertices
@@ -221,7 +249,7 @@ fn process_group_layer(
                     let cliff_points = [point_b, point_a, point_a.with_y(lift), point_b.with_y(lift)];
                     let cliff_uvs = [I16Vec2::ZERO; 4];
                     let cliff_verts = GraphicsVertex::quad(cliff_points, cliff_uvs, 0);
-                    cliff_mesh.push_quad(cliff_verts);
+                    chunk.cliff_mesh.push_quad(cliff_verts);
                 }
 
                 // Pushes western cliff vertices
@@ -230,17 +258,14 @@ fn process_group_layer(
                     let cliff_points = [point_a, point_b, point_b.with_y(lift), point_a.with_y(lift)];
                     let cliff_uvs = [I16Vec2::ZERO; 4];
                     let cliff_verts = GraphicsVertex::quad(cliff_points, cliff_uvs, 0);
-                    cliff_mesh.push_quad(cliff_verts);
+                    chunk.cliff_mesh.push_quad(cliff_verts);
                 }
 
                 gstrip = gstrip_next;
 
                 // Resets strip to ground level
                 if tile_geom.reset {
-                    gstrip.left.z -= gstrip.left.y - lift;
-                    gstrip.right.z -= gstrip.right.y - lift;
-                    gstrip.left.y = lift;
-                    gstrip.right.y = lift;
+                    gstrip = gstrip.reset(lift);
                 }
             }
         }
@@ -254,6 +279,7 @@ fn process_group_layer(
             let image = tileset.tileset.image().expect("Multi image tilesets not supported");
             let width = image.width().expect("Image did not include a width");
             let height = image.height().expect("Image did not include a height");
+            let ao_strength = parse_ao_strength(tileset.tileset.properties());
             let material = material_assets.add(StandardMaterial {
                 base_color: tileset.base_color,
                 base_color_texture: Some(tileset.base_color_texture.clone()),
@@ -267,37 +293,49 @@ fn process_group_layer(
                 cull_mode: None,
                 ..default()
             });
-            Mat { material, width, height }
+            Mat { material, width, height, ao_strength }
         })
         .collect();
 
-    // Spawns material/meshes
-    for (gmesh, mat) in gmeshes.into_iter().zip(materials) {
-        let mesh = create_bevy_mesh(
-            gmesh,
-            mat.width as f32,
-            mat.height as f32,
-            map.map.tile_width() as f32,
-            map.map.tile_height() as f32,
-        );
-        commands.entity(map_entity).with_children(|b| {
-            b.spawn(PbrBundle {
-                mesh: mesh_assets.add(mesh),
-                material: mat.material,
-                ..default()
-            });
-        });
+    // Spawns material/meshes of each chunk.
+    // Ambient occlusion of a tileset overrides that of the map.
+    let cliff_material = material_assets.add(StandardMaterial { base_color: Color::BLACK, unlit: true, ..default() });
+    let (tile_width, tile_height) = (map.map.tile_width() as f32, map.map.tile_height() as f32);
+    let map_ao_strength = parse_ao_strength(map.map.properties());
+    for chunk in chunks.into_values() {
+        for (gmesh, mat) in chunk.gmeshes.into_iter().zip(&materials) {
+            if gmesh.is_empty() { continue };
+            let ao = mat.ao_strength
+                .or(map_ao_strength)
+                .map(|strength| AmbientOcclusion { heights: &heights, strength });
+            let mesh = create_bevy_mesh(gmesh, mat.width as f32, mat.height as f32, tile_width, tile_height, ao);
+            spawn_mesh(commands, map_entity, mesh, mat.material.clone(), mesh_assets);
+        }
+        if chunk.cliff_mesh.is_empty() { continue };
+        // Cliffs are unlit black, so occluding them would have no visible effect.
+        let cliff_mesh = create_bevy_mesh(chunk.cliff_mesh, 100.0, 100.0, tile_width, tile_height, None);
+        spawn_mesh(commands, map_entity, cliff_mesh, cliff_material.clone(), mesh_assets);
     }
+}
 
-    // Spawns cliff mesh
-    let cliff_material = material_assets.add(StandardMaterial { base_color: Color::BLACK, unlit: true, ..default() });
-    let cliff_mesh = create_bevy_mesh(cliff_mesh, 100.0, 100.0, map.map.tile_width() as f32, map.map.tile_height() as f32);
+// Spawns a mesh as a child of a map, with an AABB computed up front.
+fn spawn_mesh(
+    commands: &mut Commands,
+    map_entity: Entity,
+    mesh: Mesh,
+    material: Handle<StandardMaterial>,
+    mesh_assets: &mut Assets<Mesh>,
+) {
+    let aabb = mesh.compute_aabb();
     commands.entity(map_entity).with_children(|b| {
-        b.spawn(PbrBundle {
-            mesh: mesh_assets.add(cliff_mesh),
-            material: cliff_material,
+        let mut mesh_entity = b.spawn(PbrBundle {
+            mesh: mesh_assets.add(mesh),
+            material,
             ..default()
         });
+        if let Some(aabb) = aabb {
+            mesh_entity.insert(aabb);
+        }
     });
 }
 
@@ -305,11 +343,28 @@ fn process_group_layer(
 fn process_object_layer(
     commands: &mut Commands,
     object_layer: &tp::ObjectGroupLayer,
+    map: &Map,
+    map_entity: Entity,
     tile_height: f32,
     map_height_px: f32,
     map_position: Vec3,
+    tileset_assets: &Assets<Tileset>,
+    material_assets: &mut Assets<StandardMaterial>,
 ) {
+    let mut decoration_materials = HashMap::new();  // Tileset index -> material
     for object in object_layer.objects() {
+        if object.gid().is_some() {
+            decoration::spawn_decoration(
+                commands,
+                object,
+                map,
+                map_entity,
+                map_height_px,
+                tileset_assets,
+                material_assets,
+                &mut decoration_materials,
+            );
+        }
         let props = object.properties();
         for (prop_name, prop_value) in props.iter() {
             match (prop_name, prop_value) {
@@ -355,7 +410,10 @@ fn spawn_object(
     let position = Vec3::new(object.x(), lift*tile_height, object.y() + lift*tile_height - map_height_px);
     let position = position + map_position; // Relative to map position
     let position = position + size / 2.0;   // Offset by half/size so that position is center of object
-    commands.trigger(SpawnEntity { entity_type, position, size });
+    let properties = object.properties().iter()
+        .map(|(prop_name, prop_value)| (prop_name.to_owned(), prop_value.clone()))
+        .collect();
+    commands.trigger(SpawnEntity { entity_type, position, size, properties });
 }
 
 fn parse_float(name: &str, value: &PropertyValue, default: f32) -> f32 {
@@ -408,6 +466,15 @@ impl Strip {
         }
         self
     }
+
+    // Moves strip down to the lift specified, preserving its projected position.
+    fn reset(mut self, lift: i16) -> Self {
+        self.left.z -= self.left.y - lift;
+        self.right.z -= self.right.y - lift;
+        self.left.y = lift;
+        self.right.y = lift;
+        self
+    }
 }
 
 
@@ -498,11 +565,28 @@ fn init_graphics_meshes(count: usize) -> Vec<GraphicsMesh> {
     result
 }
 
+// Graphics meshes of a square region of tiles in a group layer.
+// Splitting meshes into chunks allows them to be frustum culled individually.
+struct MeshChunk {
+    gmeshes: Vec<GraphicsMesh>,     // Parallel with the map's tileset entries
+    cliff_mesh: GraphicsMesh,
+}
+
+impl MeshChunk {
+    fn new(tileset_count: usize) -> Self {
+        Self {
+            gmeshes: init_graphics_meshes(tileset_count),
+            cliff_mesh: GraphicsMesh::new(),
+        }
+    }
+}
+
 // Material with metadata
 struct Mat {
     material: Handle<StandardMaterial>,
     width: u32,
     height: u32,
+    ao_strength: Option<f32>,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
@@ -633,6 +717,7 @@ struct TileGeom {
     shape: TileShape,
     reset: bool,        // Resets strip to ground level. Typically used when the north side is a cliff.
     cliff: Cliff,       // Which sides, if any, should emit cliff geometry.
+    surface: Surface,   // What characters walk on. Affects footsteps and speed.
 }
 
 impl TileGeom {
@@ -643,9 +728,11 @@ impl TileGeom {
                 ("shape", PropertyValue::String(shape)) => result.shape = TileShape::parse(shape),
                 ("reset", PropertyValue::Bool(reset))   => result.reset = *reset,
                 ("cliff", PropertyValue::String(cliff)) => result.cliff = Cliff::parse(cliff),
+                ("surface", PropertyValue::String(surface)) => result.surface = Surface::parse(surface),
                 ("shape", _) => panic!("Property 'shape' not a string"),
                 ("reset", _) => panic!("Property 'reset' not a bool"),
                 ("cliff", _) => panic!("Property 'cliff' not a string"),
+                ("surface", _) => panic!("Property 'surface' not a string"),
                 _ => {}
             }
         }
@@ -694,6 +781,11 @@ impl TileShape {
         }
     }
 
+    /// True if characters can walk on this shape.
+    fn is_walkable(self) -> bool {
+        !matches!(self, Self::Wall | Self::WallNE | Self::WallNW | Self::WallFloorSE | Self::WallFloorSW)
+    }
+
     fn is_flipped(self) -> bool {
         match self.quad_info() {
             QuadInfo::QuadFlipped => true,
@@ -761,6 +853,50 @@ impl Cliff {
     }
 }
 
+/// Material of a tile characters walk on.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Default, Debug)]
+pub enum Surface {
+    /// No particular material.
+    #[default]
+    Plain,
+    Grass,
+    Sand,
+    Wood,
+    Mud,
+}
+
+impl Surface {
+
+    /// Fraction of top speed while walking on the surface.
+    pub fn speed(self) -> f32 {
+        match self {
+            Self::Sand  => 0.85,
+            Self::Mud   => 0.6,
+            _           => 1.0,
+        }
+    }
+
+    /// Color of the dust kicked up by footsteps, if any.
+    pub fn dust_color(self) -> Option<Color> {
+        match self {
+            Self::Sand  => Some(Color::srgba(0.9, 0.8, 0.6, 0.8)),
+            Self::Mud   => Some(Color::srgba(0.4, 0.3, 0.2, 0.8)),
+            _           => None,
+        }
+    }
+
+    fn parse(surface: &str) -> Self {
+        match surface {
+            "plain"     => Self::Plain,
+            "grass"     => Self::Grass,
+            "sand"      => Self::Sand,
+            "wood"      => Self::Wood,
+            "mud"       => Self::Mud,
+            _ => panic!("Invalid tile surface '{surface}'"),
+        }
+    }
+}
+
 pub mod messages {
 
     use bevy::prelude::*;
@@ -0,0 +1,288 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use bevy::ecs::system::SystemParam;
+use bevy::math::I16Vec3;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use super::{Cliff, GroupMeta, RegularTileLayer, Strip, Surface, TH, THH};
+
+/// Maximum number of cells A* will expand before giving up.
+const MAX_EXPANDED_CELLS: usize = 8192;
+const ORTHOGONAL_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+
+
+/// Walkable cells of a single map, built from the collision geometry of its group layers.
+/// Cells are addressed with global coordinates so that grids of neighboring maps line up.
+/// A coordinate's x is the tile column, and its y is the tile row, increasing northwards.
+#[derive(Component, Clone, Debug)]
+pub struct NavGrid {
+    origin: IVec2,          // Global coordinates of the map's south-west cell.
+    width: u32,
+    height: u32,
+    tile_size: Vec2,
+    cells: Vec<Option<NavCell>>,
+}
+
+impl NavGrid {
+
+    pub(super) fn new(map_position: Vec3, width: u32, height: u32, tile_size: Vec2) -> Self {
+        let origin = IVec2::new(
+            (map_position.x / tile_size.x).round() as i32,
+            ((map_position.y - map_position.z) / tile_size.y).round() as i32,
+        );
+        Self {
+            origin,
+            width,
+            height,
+            tile_size,
+            cells: vec![None; (width * height) as usize],
+        }
+    }
+
+    /// Cell at the global coordinates specified.
+    /// [`None`] if out of bounds or not walkable.
+    pub fn get(&self, coords: IVec2) -> Option<NavCell> {
+        let local = coords - self.origin;
+        if local.x < 0 || local.y < 0 || local.x >= self.width as i32 || local.y >= self.height as i32 {
+            return None;
+        }
+        self.cells[(local.y as u32 * self.width + local.x as u32) as usize]
+    }
+
+    /// True if the global coordinates specified are within this grid.
+    pub fn contains(&self, coords: IVec2) -> bool {
+        let local = coords - self.origin;
+        local.x >= 0 && local.y >= 0 && local.x < self.width as i32 && local.y < self.height as i32
+    }
+
+    pub(super) fn set(&mut self, local: IVec2, cell: Option<NavCell>) {
+        if local.x < 0 || local.y < 0 || local.x >= self.width as i32 || local.y >= self.height as i32 {
+            return;
+        }
+        self.cells[(local.y as u32 * self.width + local.x as u32) as usize] = cell;
+    }
+
+    /// Writes cells of a group layer into the grid.
+    /// Cells overwrite those of group layers inserted previously.
+    pub(super) fn insert_group_layer(&mut self, regular_layers: &[RegularTileLayer], group_meta: &GroupMeta) {
+        let scale_y = self.tile_size.y / TH as f32;
+        for layer in regular_layers {
+            let region = layer.region;
+            let (min_x, max_x) = (region.x, region.x + region.width as i32);
+            let (min_y, max_y) = (region.y, region.y + region.height as i32);
+            for tile_x in min_x..max_x {
+                let tile_x = tile_x as i16;
+                let lift = group_meta.lift * TH;
+                let mut strip = Strip {
+                    left: I16Vec3::new(tile_x, lift, lift),
+                    right: I16Vec3::new(tile_x+1, lift, lift),
+                };
+                for tile_y in (min_y..max_y).rev() {
+                    let tile_coords = (tile_x, tile_y as i16);
+                    let tile_geom = group_meta.collision_geoms.get(&tile_coords).copied().unwrap_or_default();
+                    let strip_next = strip.next(tile_geom.shape);
+                    if layer.tiles.contains_key(&tile_coords) {
+                        let local = IVec2::new(tile_x as i32, self.height as i32 - 1 - tile_y);
+                        let cell = match tile_geom.shape.is_walkable() {
+                            true => Some(NavCell {
+                                south: (strip.left.y + strip.right.y) as f32 / 2.0 * scale_y,
+                                north: (strip_next.left.y + strip_next.right.y) as f32 / 2.0 * scale_y,
+                                cliff: tile_geom.cliff,
+                                surface: tile_geom.surface,
+                            }),
+                            false => None,
+                        };
+                        self.set(local, cell);
+                    }
+                    strip = strip_next;
+                    if tile_geom.reset {
+                        strip = strip.reset(lift);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A single walkable cell in a [`NavGrid`].
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct NavCell {
+    /// Height of the cell's southern edge.
+    pub south: f32,
+    /// Height of the cell's northern edge.
+    pub north: f32,
+    /// What characters walk on.
+    pub surface: Surface,
+    pub(super) cliff: Cliff,
+}
+
+impl NavCell {
+    /// Height at the center of the cell.
+    pub fn height(&self) -> f32 {
+        (self.south + self.north) / 2.0
+    }
+}
+
+/// Queries the [`NavGrid`]s of all spawned maps as if they were a single grid.
+#[derive(SystemParam)]
+pub struct Navigation<'w, 's> {
+    grids: Query<'w, 's, &'static NavGrid>,
+}
+
+impl<'w, 's> Navigation<'w, 's> {
+
+    /// Cell at the global coordinates specified.
+    pub fn cell(&self, coords: IVec2) -> Option<NavCell> {
+        self.grids.iter()
+            .find(|grid| grid.contains(coords))
+            .and_then(|grid| grid.get(coords))
+    }
+
+    /// Global coordinates of the cell a world position is over.
+    pub fn coords_of(&self, position: Vec3) -> Option<IVec2> {
+        let tile_size = self.tile_size()?;
+        Some(IVec2::new(
+            (position.x / tile_size.x).floor() as i32,
+            ((position.y - position.z) / tile_size.y).floor() as i32,
+        ))
+    }
+
+    /// Cell a world position is over.
+    pub fn cell_at(&self, position: Vec3) -> Option<NavCell> {
+        self.cell(self.coords_of(position)?)
+    }
+
+    /// Surface a world position is over.
+    /// [`None`] if not over a walkable cell.
+    pub fn surface_at(&self, position: Vec3) -> Option<Surface> {
+        self.cell_at(position).map(|cell| cell.surface)
+    }
+
+    /// Position on the surface of the center of a cell.
+    pub fn center_of(&self, coords: IVec2) -> Option<Vec3> {
+        let tile_size = self.tile_size()?;
+        let cell = self.cell(coords)?;
+        let height = cell.height();
+        let projected = (coords.y as f32 + 0.5) * tile_size.y;
+        Some(Vec3::new((coords.x as f32 + 0.5) * tile_size.x, height, height - projected))
+    }
+
+    /// True if a character can move directly from one cell to a neighboring cell.
+    /// Steps must not cross cliff edges, walls or steep changes in height.
+    pub fn can_step(&self, from: IVec2, to: IVec2) -> bool {
+        let step = to - from;
+        if step.x.abs() > 1 || step.y.abs() > 1 || step == IVec2::ZERO { return false };
+        if step.x != 0 && step.y != 0 {
+            // Diagonals may not cut corners
+            let via_x = from + IVec2::new(step.x, 0);
+            let via_y = from + IVec2::new(0, step.y);
+            return
+                self.can_step(from, via_x) && self.can_step(via_x, to) &&
+                self.can_step(from, via_y) && self.can_step(via_y, to);
+        }
+        let (Some(a), Some(b)) = (self.cell(from), self.cell(to)) else { return false };
+        let Some(tile_size) = self.tile_size() else { return false };
+        const EPS: f32 = 0.01;
+        let max_side_step = tile_size.y * (THH as f32 / TH as f32) / 2.0 + EPS;
+        match (step.x, step.y) {
+            (0, 1)  => !a.cliff.contains(Cliff::NORTH) && (a.north - b.south).abs() < EPS,
+            (0, -1) => !b.cliff.contains(Cliff::NORTH) && (a.south - b.north).abs() < EPS,
+            (1, 0)  => !a.cliff.contains(Cliff::EAST) && !b.cliff.contains(Cliff::WEST) && (a.height() - b.height()).abs() <= max_side_step,
+            (-1, 0) => !a.cliff.contains(Cliff::WEST) && !b.cliff.contains(Cliff::EAST) && (a.height() - b.height()).abs() <= max_side_step,
+            _ => false,
+        }
+    }
+
+    /// Finds the shortest path between two world positions using A*.
+    /// Resulting path consists of cell centers, starting with the cell of the start position.
+    /// Returns [`None`] if either position is not walkable, or if no path was found.
+    pub fn find_path(&self, start: Vec3, goal: Vec3) -> Option<Vec<Vec3>> {
+        let start = self.coords_of(start)?;
+        let goal = self.coords_of(goal)?;
+        let path = self.find_path_coords(start, goal)?;
+        path.into_iter().map(|coords| self.center_of(coords)).collect()
+    }
+
+    /// Finds the shortest path between two cells using A*.
+    /// Resulting path includes both the start and goal cells.
+    pub fn find_path_coords(&self, start: IVec2, goal: IVec2) -> Option<Vec<IVec2>> {
+        self.cell(start)?;
+        self.cell(goal)?;
+        let mut open = BinaryHeap::new();
+        let mut came_from: HashMap<IVec2, IVec2> = HashMap::new();
+        let mut costs: HashMap<IVec2, u32> = HashMap::new();
+        open.push(Reverse((heuristic(start, goal), 0, start.x, start.y)));
+        costs.insert(start, 0);
+        let mut expanded = 0;
+        while let Some(Reverse((_, cost, x, y))) = open.pop() {
+            let current = IVec2::new(x, y);
+            if cost > costs[&current] { continue };     // Stale entry, cell was reached more cheaply since
+            if current == goal {
+                let mut path = vec![current];
+                let mut current = current;
+                while let Some(previous) = came_from.get(&current) {
+                    current = *previous;
+                    path.push(current);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            expanded += 1;
+            if expanded > MAX_EXPANDED_CELLS { return None };
+            let current_cost = costs[&current];
+            for step in NEIGHBOR_STEPS {
+                let neighbor = current + step;
+                if !self.can_step(current, neighbor) { continue };
+                let step_cost = if step.x != 0 && step.y != 0 { DIAGONAL_COST } else { ORTHOGONAL_COST };
+                let neighbor_cost = current_cost + step_cost;
+                if costs.get(&neighbor).is_some_and(|cost| *cost <= neighbor_cost) { continue };
+                costs.insert(neighbor, neighbor_cost);
+                came_from.insert(neighbor, current);
+                open.push(Reverse((neighbor_cost + heuristic(neighbor, goal), neighbor_cost, neighbor.x, neighbor.y)));
+            }
+        }
+        None
+    }
+
+    /// Tile size shared by all grids.
+    /// [`None`] if grids of different tile sizes are spawned, since their coordinates would not line up.
+    fn tile_size(&self) -> Option<Vec2> {
+        let mut grids = self.grids.iter();
+        let tile_size = grids.next()?.tile_size;
+        grids.all(|grid| grid.tile_size == tile_size).then_some(tile_size)
+    }
+}
+
+/// Warns when a map is loaded whose tile size differs from that of the other maps.
+/// Navigation is unavailable while such maps are loaded together.
+pub fn check_tile_sizes(added: Query<(Entity, &NavGrid), Added<NavGrid>>, grids: Query<(Entity, &NavGrid)>) {
+    for (entity, added_grid) in &added {
+        let mismatched = grids.iter().find(|(other, grid)| *other != entity && grid.tile_size != added_grid.tile_size);
+        if let Some((_, grid)) = mismatched {
+            bevy::log::warn!(
+                "Map {entity} has a tile size of {}, but another map has {}. All maps in an area must share the same tile size",
+                added_grid.tile_size,
+                grid.tile_size,
+            );
+        }
+    }
+}
+
+const NEIGHBOR_STEPS: [IVec2; 8] = [
+    IVec2::new(0, 1),
+    IVec2::new(1, 0),
+    IVec2::new(0, -1),
+    IVec2::new(-1, 0),
+    IVec2::new(1, 1),
+    IVec2::new(1, -1),
+    IVec2::new(-1, -1),
+    IVec2::new(-1, 1),
+];
+
+// Octile distance between two cells.
+fn heuristic(a: IVec2, b: IVec2) -> u32 {
+    let diff = (a - b).abs();
+    let (min, max) = (diff.x.min(diff.y) as u32, diff.x.max(diff.y) as u32);
+    DIAGONAL_COST * min + ORTHOGONAL_COST * (max - min)
+}
@@ -0,0 +1,152 @@
+use bevy::ecs::system::SystemState;
+use bevy::prelude::*;
+use crate::map::{Cliff, NavCell, NavGrid, Navigation, Surface};
+
+const TILE_SIZE: Vec2 = Vec2::new(16.0, 16.0);
+
+
+fn flat(height: f32) -> Option<NavCell> {
+    Some(NavCell { south: height, north: height, surface: Surface::default(), cliff: Cliff::empty() })
+}
+
+fn slope(south: f32, north: f32) -> Option<NavCell> {
+    Some(NavCell { south, north, surface: Surface::default(), cliff: Cliff::empty() })
+}
+
+fn with_cliff(cell: Option<NavCell>, cliff: Cliff) -> Option<NavCell> {
+    cell.map(|cell| NavCell { cliff, ..cell })
+}
+
+/// Grid of flat cells, with its south-west cell at the global coordinates specified.
+fn flat_grid(origin: IVec2, width: u32, height: u32) -> NavGrid {
+    let map_position = Vec3::new(origin.x as f32 * TILE_SIZE.x, 0.0, -origin.y as f32 * TILE_SIZE.y);
+    let mut grid = NavGrid::new(map_position, width, height, TILE_SIZE);
+    for x in 0..width as i32 {
+        for y in 0..height as i32 {
+            grid.set(IVec2::new(x, y), flat(0.0));
+        }
+    }
+    grid
+}
+
+/// Runs a function with a [`Navigation`] over the grids specified.
+fn with_navigation<R>(grids: impl IntoIterator<Item = NavGrid>, f: impl FnOnce(&Navigation) -> R) -> R {
+    let mut world = World::new();
+    for grid in grids {
+        world.spawn(grid);
+    }
+    let mut state = SystemState::<Navigation>::new(&mut world);
+    let navigation = state.get(&world);
+    f(&navigation)
+}
+
+
+#[test]
+fn finds_straight_path() {
+    let grid = flat_grid(IVec2::ZERO, 8, 8);
+    let path = with_navigation([grid], |nav| nav.find_path_coords(IVec2::new(0, 0), IVec2::new(4, 0)));
+    let expected: Vec<IVec2> = (0..=4).map(|x| IVec2::new(x, 0)).collect();
+    assert_eq!(Some(expected), path);
+}
+
+#[test]
+fn finds_diagonal_path() {
+    let grid = flat_grid(IVec2::ZERO, 8, 8);
+    let path = with_navigation([grid], |nav| nav.find_path_coords(IVec2::new(0, 0), IVec2::new(3, 3)));
+    let expected: Vec<IVec2> = (0..=3).map(|i| IVec2::new(i, i)).collect();
+    assert_eq!(Some(expected), path);
+}
+
+#[test]
+fn paths_around_walls() {
+    let mut grid = flat_grid(IVec2::ZERO, 5, 5);
+    for y in 0..4 {
+        grid.set(IVec2::new(2, y), None);
+    }
+    let path = with_navigation([grid], |nav| nav.find_path_coords(IVec2::new(0, 0), IVec2::new(4, 0))).unwrap();
+    assert_eq!(Some(&IVec2::new(0, 0)), path.first());
+    assert_eq!(Some(&IVec2::new(4, 0)), path.last());
+    assert!(path.contains(&IVec2::new(2, 4)));
+}
+
+#[test]
+fn fails_when_unreachable() {
+    let mut grid = flat_grid(IVec2::ZERO, 5, 5);
+    for y in 0..5 {
+        grid.set(IVec2::new(2, y), None);
+    }
+    let path = with_navigation([grid], |nav| nav.find_path_coords(IVec2::new(0, 0), IVec2::new(4, 0)));
+    assert_eq!(None, path);
+}
+
+#[test]
+fn diagonals_do_not_cut_corners() {
+    let mut grid = flat_grid(IVec2::ZERO, 3, 3);
+    grid.set(IVec2::new(1, 0), None);
+    with_navigation([grid], |nav| {
+        assert!(!nav.can_step(IVec2::new(0, 0), IVec2::new(1, 1)));
+        assert!(nav.can_step(IVec2::new(0, 1), IVec2::new(1, 2)));
+    });
+}
+
+#[test]
+fn cliffs_block_steps() {
+    let mut grid = flat_grid(IVec2::ZERO, 3, 3);
+    grid.set(IVec2::new(1, 1), with_cliff(flat(0.0), Cliff::NORTH | Cliff::EAST));
+    with_navigation([grid], |nav| {
+        let center = IVec2::new(1, 1);
+        assert!(!nav.can_step(center, IVec2::new(1, 2)));
+        assert!(!nav.can_step(IVec2::new(1, 2), center));
+        assert!(!nav.can_step(center, IVec2::new(2, 1)));
+        assert!(!nav.can_step(IVec2::new(2, 1), center));
+        assert!(nav.can_step(center, IVec2::new(0, 1)));
+        assert!(nav.can_step(center, IVec2::new(1, 0)));
+    });
+}
+
+#[test]
+fn slopes_connect_heights() {
+    let mut grid = flat_grid(IVec2::ZERO, 3, 3);
+    grid.set(IVec2::new(0, 1), slope(0.0, 16.0));
+    grid.set(IVec2::new(0, 2), flat(16.0));
+    grid.set(IVec2::new(1, 1), flat(16.0));
+    with_navigation([grid], |nav| {
+        // Up the slope
+        assert!(nav.can_step(IVec2::new(0, 0), IVec2::new(0, 1)));
+        assert!(nav.can_step(IVec2::new(0, 1), IVec2::new(0, 2)));
+        // Straight up a full tile
+        assert!(!nav.can_step(IVec2::new(1, 0), IVec2::new(1, 1)));
+        // Off the side of the slope, a half tile up or down
+        assert!(!nav.can_step(IVec2::new(0, 1), IVec2::new(1, 1)));
+    });
+}
+
+#[test]
+fn paths_across_neighboring_maps() {
+    let west = flat_grid(IVec2::new(0, 0), 4, 4);
+    let east = flat_grid(IVec2::new(4, 0), 4, 4);
+    let path = with_navigation([west, east], |nav| nav.find_path_coords(IVec2::new(1, 1), IVec2::new(6, 1)));
+    let expected: Vec<IVec2> = (1..=6).map(|x| IVec2::new(x, 1)).collect();
+    assert_eq!(Some(expected), path);
+}
+
+#[test]
+fn finds_path_between_world_positions() {
+    let grid = flat_grid(IVec2::ZERO, 4, 4);
+    let start = Vec3::new(8.0, 0.0, -8.0);
+    let goal = Vec3::new(56.0, 0.0, -8.0);
+    let path = with_navigation([grid], |nav| nav.find_path(start, goal)).unwrap();
+    assert_eq!(4, path.len());
+    assert_eq!(Vec3::new(8.0, 0.0, -8.0), path[0]);
+    assert_eq!(Vec3::new(56.0, 0.0, -8.0), path[3]);
+}
+
+#[test]
+fn mismatched_tile_sizes_disable_navigation() {
+    let west = flat_grid(IVec2::new(0, 0), 4, 4);
+    let east = NavGrid::new(Vec3::new(4.0 * TILE_SIZE.x, 0.0, 0.0), 4, 4, TILE_SIZE * 2.0);
+    with_navigation([west, east], |nav| {
+        assert_eq!(None, nav.find_path_coords(IVec2::new(0, 0), IVec2::new(2, 0)));
+        assert_eq!(None, nav.surface_at(Vec3::new(8.0, 0.0, -8.0)));
+    });
+}
@@ -3,18 +3,22 @@ use std::f32::consts::{SQRT_2, TAU};
 use std::time::Duration;
 use bevy::prelude::*;
 use crate::area::AreaLocal;
+use crate::combat::Hurtbox;
 use crate::common::CommonAssets;
+use crate::stats::{BaseStats, Stats};
+use crate::state_machine::StateMachine;
+use crate::state_machine::messages::EnterState;
 use crate::daynight::{GameTime, TIME_FRAC_MORNING, TIME_FRAC_NIGHT};
 
 const FIREFLY_BODY_SIZE: Vec3 = Vec3::new(1.2, 1.2/SQRT_2, 1.2/SQRT_2);
 const FIREFLY_LIGHT_INTENSITY: f32 = 20_000_000.0;
 const FIREFLY_MOVE_HEIGHT: f32 = 3.0;
+const FIREFLY_HURTBOX_SIZE: Vec3 = Vec3::splat(4.0);
 
 #[derive(Component)]
 pub struct Firefly {
     home: Vec3,
     timer: Timer,
-    behavior: FireflyBehavior,
     fly_radius: f32,
     scale: f32,
     sphere_id: Entity,
@@ -31,7 +35,6 @@ impl Firefly {
         Self {
             home,
             timer,
-            behavior: FireflyBehavior::Flying,
             fly_radius: radius,
             scale: 1.0,
             sphere_id
@@ -39,7 +42,8 @@ impl Firefly {
     }
 }
 
-/// Current behavior of a [`Firefly`]
+/// Current behavior of a [`Firefly`].
+/// Driven by a [`StateMachine`].
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum FireflyBehavior {
     Flying,     // Regular flying. Triggered when revealing finishes.
@@ -69,40 +73,31 @@ pub fn spawn_firefly(
     let sphere_id = commands.spawn(sphere).id();
     // Firefly
     let mut firefly = Firefly::new(sphere_id, position);
+    let mut behavior = FireflyBehavior::Flying;
     if time_frac >= TIME_FRAC_MORNING && time_frac < TIME_FRAC_NIGHT {
-        firefly.behavior = FireflyBehavior::Hidden;
+        behavior = FireflyBehavior::Hidden;
         firefly.scale = 0.0;
         light.visibility = Visibility::Hidden;
     }
     commands
-        .spawn((Name::new("firefly"), firefly, light, AreaLocal::default()))
+        .spawn((Name::new("firefly"), firefly, StateMachine::new(behavior), light, AreaLocal::default()))
+        .insert(Hurtbox { size: FIREFLY_HURTBOX_SIZE, ..default() })
+        .insert(Stats::new(BaseStats { max_hp: 1.0, ..default() }))
         .add_child(sphere_id);
 }
 
 
 /// Updates all firefly entities each frame.
 pub fn update_fireflies(
-    mut fireflies: Query<(&mut Firefly, &mut PointLight, &mut Transform, &mut Visibility)>,
+    mut fireflies: Query<(&mut Firefly, &mut StateMachine<FireflyBehavior>, &mut PointLight, &mut Transform)>,
     mut firefly_bodies: Query<&mut Transform, Without<Firefly>>,
     game_time: Res<GameTime>,
     time: Res<Time>,
 ) {
 
-    if game_time.time_just_passed(TIME_FRAC_MORNING) {
-        for (mut firefly, _, _, _) in &mut fireflies {
-            firefly.behavior = FireflyBehavior::Hiding;
-        }
-    }
-    else if game_time.time_just_passed(TIME_FRAC_NIGHT) {
-        for (mut firefly, _, _, mut visibility) in &mut fireflies {
-            firefly.behavior = FireflyBehavior::Revealing;
-            *visibility = Visibility::Inherited;
-        }
-    }
-
     // Controls non-hidden fireflies.
-    for (mut firefly, _, mut transf, _) in &mut fireflies {
-        if firefly.behavior == FireflyBehavior::Hidden { continue };
+    for (mut firefly, machine, _, mut transf) in &mut fireflies {
+        if machine.is(FireflyBehavior::Hidden) { continue };
         firefly.timer.tick(time.delta());
         let radians = TAU * firefly.timer.fraction();
         let offset = Vec3::new(radians.cos(), (radians * 3.0).sin(), radians.sin());
@@ -111,8 +106,8 @@ pub fn update_fireflies(
     }
 
     // Controls fireflies that are hiding or revealing.
-    for (mut firefly, mut light, _, mut visibility) in &mut fireflies {
-        match firefly.behavior {
+    for (mut firefly, mut machine, mut light, _) in &mut fireflies {
+        match machine.current() {
             FireflyBehavior::Hiding => {
                 firefly.scale -= 1.0 * time.delta_seconds();
                 firefly.scale = firefly.scale.max(0.0);
@@ -120,8 +115,7 @@ pub fn update_fireflies(
                 let mut body_transf = firefly_bodies.get_mut(firefly.sphere_id).unwrap();
                 body_transf.scale = FIREFLY_BODY_SIZE * firefly.scale;
                 if firefly.scale == 0.0 {
-                    firefly.behavior = FireflyBehavior::Hidden;
-                    *visibility = Visibility::Hidden;
+                    machine.set(FireflyBehavior::Hidden);
                 }
             },
             FireflyBehavior::Revealing => {
@@ -131,10 +125,35 @@ pub fn update_fireflies(
                 let mut body_transf = firefly_bodies.get_mut(firefly.sphere_id).unwrap();
                 body_transf.scale = FIREFLY_BODY_SIZE * firefly.scale;
                 if firefly.scale == 1.0 {
-                    firefly.behavior = FireflyBehavior::Flying;
+                    machine.set(FireflyBehavior::Flying);
                 }
             },
             _ => {}
         }
     }
+
+    // Hides in the morning, and reveals at night.
+    if game_time.time_just_passed(TIME_FRAC_MORNING) {
+        for (_, mut machine, _, _) in &mut fireflies {
+            machine.set(FireflyBehavior::Hiding);
+        }
+    }
+    else if game_time.time_just_passed(TIME_FRAC_NIGHT) {
+        for (_, mut machine, _, _) in &mut fireflies {
+            machine.set(FireflyBehavior::Revealing);
+        }
+    }
+}
+
+/// Shows fireflies when they start revealing, and hides them once hidden.
+pub fn enter_firefly_behavior(
+    trigger: Trigger<EnterState<FireflyBehavior>>,
+    mut fireflies: Query<&mut Visibility, With<Firefly>>,
+) {
+    let Ok(mut visibility) = fireflies.get_mut(trigger.entity()) else { return };
+    match trigger.event().state {
+        FireflyBehavior::Hidden => *visibility = Visibility::Hidden,
+        FireflyBehavior::Revealing => *visibility = Visibility::Inherited,
+        _ => {}
+    }
 }
\ No newline at end of file
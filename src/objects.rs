@@ -1,7 +1,32 @@
 use bevy::prelude::*;
+use bevy::utils::HashMap;
+use tiled_parser::PropertyValue;
 use crate::area::AreaLocal;
 use crate::common::CommonAssets;
+use crate::player::CharacterController;
+use crate::water::{Water, WaterDepth};
 
+
+/// How a [`ForceZone`] pushes characters inside it.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ForceKind {
+    /// Pushes with a force. Heavier characters are pushed less.
+    Wind,
+    /// Carries characters on the ground at a fixed velocity, regardless of mass.
+    Conveyor,
+}
+
+/// A region that pushes [`CharacterController`]s inside it every frame.
+#[derive(Component, Copy, Clone, PartialEq, Debug)]
+pub struct ForceZone {
+    /// Size of the zone's AABB, centered on its translation.
+    pub size: Vec3,
+    pub push: Vec3,
+    pub kind: ForceKind,
+}
+
+/// Spawns a body of water.
+/// Water is deep if its depth is at least [`DEEP_WATER_DEPTH`](crate::water::DEEP_WATER_DEPTH).
 pub fn spawn_water(
     commands: &mut Commands,
     position: Vec3,
@@ -19,6 +44,7 @@ pub fn spawn_water(
     };
     commands.spawn((
         Name::new("Water"),
+        Water { size, depth: WaterDepth::from_depth(size.y) },
         PbrBundle {
             mesh: common_assets.meshes.plane.clone(),
             material: assets.add(material),
@@ -31,4 +57,55 @@ pub fn spawn_water(
         },
         AreaLocal { size: Vec2::new(size.x, size.y + size.z) },
     ));
-}
\ No newline at end of file
+}
+
+/// Spawns a [`ForceZone`].
+/// Pushes along the "push_x" and "push_z" properties.
+pub fn spawn_force_zone(
+    commands: &mut Commands,
+    position: Vec3,
+    size: Vec3,
+    kind: ForceKind,
+    properties: &HashMap<String, PropertyValue>,
+) {
+    let mut push = Vec3::ZERO;
+    for (prop_name, prop_value) in properties {
+        match (prop_name.as_str(), prop_value) {
+            ("push_x", PropertyValue::Float(value))     => push.x = *value,
+            ("push_x", PropertyValue::Int(value))       => push.x = *value as f32,
+            ("push_z", PropertyValue::Float(value))     => push.z = *value,
+            ("push_z", PropertyValue::Int(value))       => push.z = *value as f32,
+            ("push_x", _) => panic!("Property 'push_x' not a number"),
+            ("push_z", _) => panic!("Property 'push_z' not a number"),
+            _ => {}
+        }
+    }
+    let name = match kind {
+        ForceKind::Wind => "wind",
+        ForceKind::Conveyor => "conveyor",
+    };
+    commands.spawn((
+        Name::new(name),
+        ForceZone { size, push, kind },
+        SpatialBundle::from_transform(Transform::from_translation(position)),
+        AreaLocal { size: Vec2::new(size.x, size.y + size.z) },
+    ));
+}
+
+/// Pushes characters inside force zones.
+pub fn apply_force_zones(
+    zones: Query<(&ForceZone, &GlobalTransform)>,
+    mut characters: Query<(&mut CharacterController, &GlobalTransform)>,
+) {
+    for (zone, zone_transf) in &zones {
+        for (mut cc, transf) in &mut characters {
+            let dist = (transf.translation() - zone_transf.translation()).abs();
+            let max_dist = zone.size / 2.0;
+            if dist.x > max_dist.x || dist.y > max_dist.y || dist.z > max_dist.z { continue };
+            match zone.kind {
+                ForceKind::Wind => cc.apply_force(zone.push),
+                ForceKind::Conveyor => cc.apply_carry(zone.push),
+            }
+        }
+    }
+}
@@ -4,94 +4,232 @@ use std::time::Duration;
 use bevy::input::gamepad::{GamepadConnection, GamepadConnectionEvent, GamepadEvent};
 use bevy::prelude::*;
 use bevy::sprite::Anchor;
+use smallvec::SmallVec;
 
 
 use messages::SpawnPlayer;
+use crate::action::tween::Ease;
 use crate::animation::{Animation, AnimationBundle, AnimationSet, AnimationState};
 use crate::area::AreaStreamer;
+use crate::combat::{spawn_hitbox, Hitbox, Hurtbox};
 use crate::common::CommonAssets;
-use crate::input::{GamepadMapping, KeyboardMapping, StickConfig, StickType, VButtons, VSticks};
-use crate::equipment::{Equipment, Hair, HairKind, Outfit};
+use crate::footstep::Footsteps;
+use crate::map::{Footprint, Surface};
+use crate::interact::InteractionTarget;
+use crate::input::{GamepadMapping, InputDevice, KeyboardMapping, StickConfig, StickType, VButtons, VSticks};
+use crate::equipment::{Equipment, Hair, HairKind, Outfit, Weapon};
 use crate::messages::ToggleEquipmentMenu;
 use crate::round::Round;
+use crate::state_machine::{StateAnimation, StateAnimations, StateMachine};
+use crate::state_machine::messages::EnterState;
+use crate::stats::{BaseStats, RespawnPoint, Stats};
+use crate::trigger::ZoneActivator;
+use crate::water::{WaterContact, WaterDepth};
 use crate::EntityIndex;
 
+const ATTACK_DURATION: Duration = Duration::from_millis(300);
+const ATTACK_HITBOX_LIFETIME: Duration = Duration::from_millis(150);
+const ATTACK_HITBOX_SIZE: Vec3 = Vec3::new(20.0, 24.0, 20.0);
+const ATTACK_REACH: f32 = 14.0;
+const ATTACK_DAMAGE: f32 = 1.0;
+const ATTACK_KNOCKBACK: f32 = 6.0;
+const HURTBOX_SIZE: Vec3 = Vec3::new(12.0, 24.0, 8.0);
+const HURTBOX_OFFSET: Vec3 = Vec3::new(0.0, 12.0, 0.0);
+const IFRAMES: Duration = Duration::from_millis(1000);
+const DODGE_DURATION: Duration = Duration::from_millis(400);
+const DODGE_COOLDOWN: Duration = Duration::from_millis(300);
+const DODGE_IFRAMES: Duration = Duration::from_millis(300);
+const DODGE_DISTANCE: f32 = 48.0;
+const DODGE_EASE: Ease = Ease::QuadOut;
+/// Fraction of top speed while wading through shallow water.
+const WADE_SPEED: f32 = 0.6;
+/// Fraction of top speed while swimming in deep water.
+const SWIM_SPEED: f32 = 0.75;
+/// Hair color of each player, so that they can be told apart.
+const HAIR_COLORS: [Color; 4] = [
+    Color::linear_rgb(1.0, 1.0, 0.0),
+    Color::linear_rgb(1.0, 0.2, 0.1),
+    Color::linear_rgb(0.2, 0.4, 1.0),
+    Color::linear_rgb(0.2, 1.0, 0.3),
+];
 
 #[derive(Bundle, Default, Debug)]
 pub struct PlayerBundle {
     pub player: Player,
+    pub state_machine: StateMachine<PlayerBehavior>,
+    pub facing: Facing,
+    pub facing_mode: FacingMode,
+    pub interaction_target: InteractionTarget,
     pub equipment: Equipment,
     pub character_controller: CharacterController,
     pub vbuttons: VButtons,
     pub vsticks: VSticks,
     pub animation_bundle: AnimationBundle<StandardMaterial>,
     pub area_streamer: AreaStreamer,
+    pub zone_activator: ZoneActivator,
+    pub hurtbox: Hurtbox,
+    pub stats: Stats,
+    pub respawn_point: RespawnPoint,
+    pub water_contact: WaterContact,
     pub round: Round,
 }
 
 #[derive(Component, Copy, Clone, PartialEq, Debug)]
 pub struct Player {
-    /// Direction player visibly faces
-    pub card_dir: CardinalDirection,
-    /// What the player is currently doing
-    pub behavior: PlayerBehavior,
-    /// What the player was doing last frame
-    pub behavior_prev: PlayerBehavior,
+    /// Direction of the current or last dodge
+    pub dodge_dir: Vec3,
+    /// Distance traveled during the current or last dodge
+    pub dodge_traveled: f32,
+    /// Time remaining before the player can dodge again
+    pub dodge_cooldown: Duration,
 }
 
-#[derive(Component, Copy, Clone, PartialEq, Debug)]
+#[derive(Component, Clone, PartialEq, Debug)]
 pub struct CharacterController {
     pub top_speed: f32,
     pub velocity: Vec3,
+    /// Resistance to impulses and forces.
+    pub mass: f32,
+    /// Pushes from external sources, like knockback.
+    pub impulses: SmallVec<[Impulse; 4]>,
+    /// Sum of forces applied this frame, like wind. Cleared once applied.
+    pub force: Vec3,
+    /// Velocity of the ground the character stands on, like a conveyor. Cleared once applied.
+    pub carry: Vec3,
     pub ground_friction: f32,
     pub air_friction: f32,
+    /// Friction while swimming in deep water.
+    pub water_friction: f32,
     pub on_ground: bool,
+    /// Depth of the water the character is in, if any.
+    pub water_depth: Option<WaterDepth>,
+    /// Surface the character is over.
+    pub surface: Surface,
 }
 
 impl CharacterController {
 
     pub fn speed_friction(&self) -> (f32, f32) {
-        let friction = match self.on_ground {
-            true => self.ground_friction,
-            false => self.air_friction,
+        let friction = match (self.water_depth, self.on_ground) {
+            (Some(WaterDepth::Deep), _) => self.water_friction,
+            (_, true) => self.ground_friction,
+            (_, false) => self.air_friction,
+        };
+        let top_speed = match (self.water_depth, self.on_ground) {
+            (Some(WaterDepth::Shallow), _) => self.top_speed * WADE_SPEED,
+            (Some(WaterDepth::Deep), _) => self.top_speed * SWIM_SPEED,
+            (None, true) => self.top_speed * self.surface.speed(),
+            (None, false) => self.top_speed,
         };
-        let speed = self.top_speed/friction - self.top_speed;
+        let speed = top_speed/friction - top_speed;
         (speed, friction)
     }
+
+    /// Pushes the character, decaying with friction and the decay specified.
+    /// Heavier characters are pushed less.
+    pub fn apply_impulse(&mut self, impulse: Vec3, decay: f32) {
+        self.impulses.push(Impulse { velocity: impulse / self.mass, decay });
+    }
+
+    /// Pushes the character for this frame only.
+    /// Heavier characters are pushed less.
+    pub fn apply_force(&mut self, force: Vec3) {
+        self.force += force;
+    }
+
+    /// Moves the character with the ground for this frame only, regardless of mass.
+    /// Does nothing while off the ground.
+    pub fn apply_carry(&mut self, velocity: Vec3) {
+        if self.on_ground {
+            self.carry += velocity;
+        }
+    }
+
+    /// Removes all impulses and forces.
+    pub fn clear_external(&mut self) {
+        self.impulses.clear();
+        self.force = Vec3::ZERO;
+        self.carry = Vec3::ZERO;
+    }
+
+    /// Sums and clears forces, then decays impulses.
+    /// Returns how far they move the character this frame.
+    fn step_external(&mut self) -> Vec3 {
+        let (_, friction) = self.speed_friction();
+        let mut displacement = self.force / self.mass + self.carry;
+        for impulse in &mut self.impulses {
+            displacement += impulse.velocity;
+            impulse.velocity *= friction * impulse.decay;
+        }
+        self.impulses.retain(|impulse| impulse.velocity.length_squared() >= 0.01);
+        self.force = Vec3::ZERO;
+        self.carry = Vec3::ZERO;
+        displacement
+    }
+}
+
+/// A push on a [`CharacterController`] that decays over time.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Impulse {
+    pub velocity: Vec3,
+    /// Fraction of velocity kept each frame, on top of the character's friction.
+    /// 1.0 decays with friction alone.
+    pub decay: f32,
 }
 
 impl Default for CharacterController {
     fn default() -> Self {
          Self {
-            top_speed: 2.0,
+            top_speed: BaseStats::default().speed,
             velocity: Vec3::ZERO,
+            mass: 1.0,
+            impulses: SmallVec::new(),
+            force: Vec3::ZERO,
+            carry: Vec3::ZERO,
             ground_friction: 0.5,
             air_friction: 0.95,
+            water_friction: 0.85,
             on_ground: true,
+            water_depth: None,
+            surface: Surface::default(),
         }
     }
 }
 
 /// Current "thing" player is doing.
+/// Driven by a [`StateMachine`].
 #[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
 pub enum PlayerBehavior {
     #[default]
     Idle,
     Walking,
+    /// Swinging weapon. Movement and direction are locked until finished.
+    Attacking,
+    /// Rolling a fixed distance. Direction is locked, and damage is briefly ignored.
+    Dodging,
+    /// Moving through deep water. Can't attack or dodge.
+    Swimming,
 }
 
+/// One of 8 directions on the XZ plane.
+/// Cardinal directions come first, so that they double as the variant indices of 4-direction animations.
 #[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
-pub enum CardinalDirection {
+pub enum CompassDirection {
     #[default]
     South = 0,
     North = 1,
     East = 2,
-    West = 3
+    West = 3,
+    SouthEast = 4,
+    SouthWest = 5,
+    NorthEast = 6,
+    NorthWest = 7,
 }
 
-impl CardinalDirection {
-    
-    pub fn from_vec2(vec: Vec2) -> Option<Self> {
+impl CompassDirection {
+
+    /// Nearest cardinal direction to a vector, where +Y is north.
+    pub fn cardinal_from_vec2(vec: Vec2) -> Option<Self> {
         const SLICE: f32 = TAU / 8.0;
         if vec.length_squared() < 0.01 { return None }
         let dir = if vec.y > 0.0 {
@@ -112,14 +250,95 @@ impl CardinalDirection {
         };
         Some(dir)
     }
+
+    /// Nearest of all 8 directions to a vector, where +Y is north.
+    pub fn from_vec2(vec: Vec2) -> Option<Self> {
+        const SLICE: f32 = TAU / 8.0;
+        const COUNTER_CLOCKWISE: [CompassDirection; 8] = [
+            CompassDirection::East,
+            CompassDirection::NorthEast,
+            CompassDirection::North,
+            CompassDirection::NorthWest,
+            CompassDirection::West,
+            CompassDirection::SouthWest,
+            CompassDirection::South,
+            CompassDirection::SouthEast,
+        ];
+        if vec.length_squared() < 0.01 { return None }
+        let slice_idx = (vec.to_angle() / SLICE).round().rem_euclid(8.0) as usize;
+        Some(COUNTER_CLOCKWISE[slice_idx])
+    }
+
+    pub fn is_diagonal(self) -> bool {
+        self as usize >= 4
+    }
+
+    /// Unit vector in the direction, on the XZ plane.
+    pub fn to_vec3(self) -> Vec3 {
+        const DIAG: f32 = FRAC_1_SQRT_2;
+        match self {
+            Self::South     => Vec3::new(0.0, 0.0, 1.0),
+            Self::North     => Vec3::new(0.0, 0.0, -1.0),
+            Self::East      => Vec3::new(1.0, 0.0, 0.0),
+            Self::West      => Vec3::new(-1.0, 0.0, 0.0),
+            Self::SouthEast => Vec3::new(DIAG, 0.0, DIAG),
+            Self::SouthWest => Vec3::new(-DIAG, 0.0, DIAG),
+            Self::NorthEast => Vec3::new(DIAG, 0.0, -DIAG),
+            Self::NorthWest => Vec3::new(-DIAG, 0.0, -DIAG),
+        }
+    }
+}
+
+/// Direction a character visibly faces.
+#[derive(Component, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct Facing(pub CompassDirection);
+
+/// Directions a character can face, and which variant of a directional animation shows each of them.
+/// Characters without one face 4 directions.
+#[derive(Component, Copy, Clone, Eq, PartialEq, Debug)]
+pub struct FacingMode {
+    /// If true, diagonal directions can be faced. Otherwise, only cardinal directions can.
+    pub eight_directions: bool,
+    /// Offset of each direction's variant from the first variant, indexed by [`CompassDirection`].
+    pub offsets: [usize; 8],
+}
+
+impl FacingMode {
+    /// 4 directions, each with its own variant.
+    pub const FOUR: Self = Self {
+        eight_directions: false,
+        offsets: [0, 1, 2, 3, 2, 3, 2, 3],
+    };
+    /// 8 directions, each with its own variant.
+    pub const EIGHT: Self = Self {
+        eight_directions: true,
+        offsets: [0, 1, 2, 3, 4, 5, 6, 7],
+    };
+    /// 8 directions, using 4-direction animations.
+    /// Diagonals are shown with the east or west variant.
+    pub const EIGHT_ON_FOUR: Self = Self {
+        eight_directions: true,
+        ..Self::FOUR
+    };
+
+    /// Offset of a direction's variant from the first variant.
+    pub fn offset(&self, direction: CompassDirection) -> usize {
+        self.offsets[direction as usize]
+    }
+}
+
+impl Default for FacingMode {
+    fn default() -> Self {
+        Self::FOUR
+    }
 }
 
 impl Default for Player {
     fn default() -> Self {
         Self {
-            behavior: PlayerBehavior::default(),
-            behavior_prev: PlayerBehavior::default(),
-            card_dir: CardinalDirection::default(),
+            dodge_dir: Vec3::ZERO,
+            dodge_traveled: 0.0,
+            dodge_cooldown: Duration::ZERO,
         }
     }
 }
@@ -127,20 +346,24 @@ impl Default for Player {
 pub fn spawn_player(
     trigger: Trigger<SpawnPlayer>,
     common_assets: Res<CommonAssets>,
-    gamepads: Res<Gamepads>,
     mut entity_index: ResMut<EntityIndex>,
     mut commands: Commands,
 ) {
-
+    let message = trigger.event();
+    let player_number = entity_index.players.len();
     let player_hair = Hair {
         kind: HairKind::Ponytail,
-        color: Color::linear_rgb(1.0, 1.0, 0.0),
+        color: HAIR_COLORS[player_number % HAIR_COLORS.len()],
         brightness: 1.0
     }.into();
 
     let mut player_bundle = PlayerBundle::default();
     player_bundle.equipment.hair = Some(player_hair);
     player_bundle.equipment.outfit = Some(Outfit::Casual1.into());
+    player_bundle.equipment.weapon = Some(Weapon::WoodenAxe.into());
+    player_bundle.hurtbox = Hurtbox { size: HURTBOX_SIZE, offset: HURTBOX_OFFSET };
+    player_bundle.stats = Stats::default().with_iframes(IFRAMES);
+    player_bundle.respawn_point = RespawnPoint(message.position);
     player_bundle.area_streamer =  AreaStreamer { size: Vec2::splat(32.0 * 40.0) };
     player_bundle.vsticks = VSticks::new(2);
     player_bundle.animation_bundle.animation_set = common_assets.animations.player.clone();
@@ -150,48 +373,45 @@ pub fn spawn_player(
     let player_id = commands
         .spawn(player_bundle)
         .insert((
-            Name::new("player"),
-            Transform::from_translation(trigger.event().position),
-            KeyboardMapping::from([
-                (KeyCode::ArrowLeft,    buttons::LEFT),
-                (KeyCode::ArrowRight,   buttons::RIGHT),
-                (KeyCode::ArrowUp,      buttons::UP),
-                (KeyCode::ArrowDown,    buttons::DOWN),
-                (KeyCode::Enter,        buttons::START),
-            ]),
+            Name::new(format!("player_{}", player_number + 1)),
+            Transform::from_translation(message.position),
+            StateAnimations(player_animation),
+            Footsteps::new(animations::WALK_BASE..animations::WALK_BASE+4, [1, 4]),
+            message.device,
         ))
         .id();
-    if let Some(first_gamepad) = gamepads.iter().next() {
-        let mapping = create_gamepad_mapping(first_gamepad);
-        commands.entity(player_id).insert(mapping);
-    }
-    entity_index.player = Some(player_id);
+    match message.device {
+        InputDevice::Keyboard => commands.entity(player_id).insert(create_keyboard_mapping()),
+        InputDevice::Gamepad(gamepad) => commands.entity(player_id).insert(create_gamepad_mapping(gamepad)),
+    };
+    entity_index.players.push(player_id);
 }
 
+/// Removes players from the [`EntityIndex`] as they despawn.
+pub fn forget_player(trigger: Trigger<OnRemove, Player>, mut entity_index: ResMut<EntityIndex>) {
+    let player_id = trigger.entity();
+    entity_index.players.retain(|player| *player != player_id);
+}
 
-/// Inserts / removes a gamepad mapping to the player whenever a gamepad connects disconnects.
-/// Does nothing if already inserted.
-pub fn assign_gamepad_to_player(
+
+/// Inserts / removes the gamepad mapping of the player a gamepad belongs to whenever it connects / disconnects.
+/// Gamepads that belong to no player are ignored.
+pub fn assign_gamepads_to_players(
     mut events: EventReader<GamepadEvent>,
-    mut players: Query<Entity, With<Player>>,
-    gamepads: Res<Gamepads>,
+    players: Query<(Entity, &InputDevice), With<Player>>,
     mut commands: Commands,
 ) {
     for event in events.read() {
-        match event {
-            GamepadEvent::Connection(GamepadConnectionEvent { gamepad, connection: GamepadConnection::Connected(_) }) => {
-                let mapping = create_gamepad_mapping(gamepad.clone());
-                for player_id in &mut players {
-                    commands.entity(player_id).insert(mapping.clone());
-                }
+        let GamepadEvent::Connection(GamepadConnectionEvent { gamepad, connection }) = event else { continue };
+        let device = InputDevice::Gamepad(*gamepad);
+        let Some((player_id, _)) = players.iter().find(|(_, player_device)| **player_device == device) else { continue };
+        match connection {
+            GamepadConnection::Connected(_) => {
+                commands.entity(player_id).insert(create_gamepad_mapping(*gamepad));
             },
-            GamepadEvent::Connection(GamepadConnectionEvent { connection: GamepadConnection::Disconnected, .. }) => {
-                if gamepads.iter().count() != 0 { continue }    // Only remove if it was the last gamepad to disconnect
-                for player_id in &mut players {
-                    commands.entity(player_id).remove::<GamepadMapping>();
-                }   
+            GamepadConnection::Disconnected => {
+                commands.entity(player_id).remove::<GamepadMapping>();
             },
-            _ => {}
         }
     }
 }
@@ -200,18 +420,25 @@ pub fn update_players(
     mut commands: Commands,
     mut players: Query<(
         &mut Player,
+        &mut StateMachine<PlayerBehavior>,
+        &mut Facing,
+        &FacingMode,
         &mut CharacterController,
         &VButtons,
-        &VSticks
-    )>
+        &VSticks,
+    )>,
+    time: Res<Time>,
 ) {
-    for (mut player, mut cc, buttons, sticks) in &mut players {
+    for (mut player, mut machine, mut facing, facing_mode, mut cc, buttons, sticks) in &mut players {
 
         // Determines player's travel direction.
-        // Determines player's cardinal direction.
+        // Determines player's facing direction.
         // Uses either dpad or stick.
+        // Both are locked while attacking or dodging.
         let mut direction = Vec3::ZERO;
+        let can_move = !matches!(machine.current(), PlayerBehavior::Attacking | PlayerBehavior::Dodging);
         let using_dpad = buttons.pressed(buttons::LEFT | buttons::RIGHT | buttons::UP | buttons::DOWN);
+        let using_dpad = can_move && using_dpad;
         if using_dpad {
             const DIAG: f32 = FRAC_1_SQRT_2;
             let (x, y) = xy_from_dpad(buttons.pressed);
@@ -227,16 +454,25 @@ pub fn update_players(
                 (1, -1) => Vec3::new(DIAG, 0.0, DIAG),
                 _ => Vec3::ZERO,
             };
-            if let Some(card_dir) = card_dir_from_xy(x, y, prev_x, prev_y) {
-                player.card_dir = card_dir;
+            let facing_dir = match facing_mode.eight_directions {
+                true => CompassDirection::from_vec2(Vec2::new(x as f32, y as f32)),
+                false => card_dir_from_xy(x, y, prev_x, prev_y),
+            };
+            if let Some(facing_dir) = facing_dir {
+                facing.0 = facing_dir;
             }
         }
-        else {
+        else if can_move {
             let stick = sticks.get(sticks::LEFT).unwrap();
             let stick = Vec3::new(stick.x, 0.0, -stick.y);
             direction = (direction + stick).clamp_length_max(1.0);
-            if let Some(card_dir) = CardinalDirection::from_vec2(Vec2::new(direction.x, -direction.z)) {
-                player.card_dir = card_dir;
+            let stick_dir = Vec2::new(direction.x, -direction.z);
+            let facing_dir = match facing_mode.eight_directions {
+                true => CompassDirection::from_vec2(stick_dir),
+                false => CompassDirection::cardinal_from_vec2(stick_dir),
+            };
+            if let Some(facing_dir) = facing_dir {
+                facing.0 = facing_dir;
             }
         }
 
@@ -250,21 +486,32 @@ pub fn update_players(
             is_moving = false;
         }
 
-        // Updates behavior
-        player.behavior = match player.behavior {
-            PlayerBehavior::Idle => {
-                match is_moving {
-                    false => PlayerBehavior::Idle,
-                    true => PlayerBehavior::Walking,
-                }
-            },
-            PlayerBehavior::Walking => {
-                match is_moving {
-                    false => PlayerBehavior::Idle,
-                    true => PlayerBehavior::Walking,
-                }
-            },
+        // Decides next behavior
+        player.dodge_cooldown = player.dodge_cooldown.saturating_sub(time.delta());
+        let attack_pressed = buttons.just_pressed(buttons::ATTACK);
+        let dodge_pressed = buttons.just_pressed(buttons::DODGE) && player.dodge_cooldown.is_zero();
+        let moving_behavior = match (cc.water_depth, is_moving) {
+            (Some(WaterDepth::Deep), _) => PlayerBehavior::Swimming,
+            (_, false) => PlayerBehavior::Idle,
+            (_, true) => PlayerBehavior::Walking,
+        };
+        let next = match machine.current() {
+            PlayerBehavior::Idle | PlayerBehavior::Walking if attack_pressed => PlayerBehavior::Attacking,
+            PlayerBehavior::Idle | PlayerBehavior::Walking if dodge_pressed => PlayerBehavior::Dodging,
+            PlayerBehavior::Idle | PlayerBehavior::Walking | PlayerBehavior::Swimming => moving_behavior,
+            PlayerBehavior::Attacking if machine.elapsed() < ATTACK_DURATION => PlayerBehavior::Attacking,
+            PlayerBehavior::Dodging if machine.elapsed() < DODGE_DURATION => PlayerBehavior::Dodging,
+            PlayerBehavior::Attacking | PlayerBehavior::Dodging => moving_behavior,
         };
+        machine.set(next);
+
+        // Moves along the dodge curve, ignoring friction
+        if machine.is(PlayerBehavior::Dodging) {
+            let t = machine.elapsed().as_secs_f32() / DODGE_DURATION.as_secs_f32();
+            let distance = DODGE_DISTANCE * DODGE_EASE.sample(t);
+            cc.velocity = player.dodge_dir * (distance - player.dodge_traveled);
+            player.dodge_traveled = distance;
+        }
 
         // Opens equipment menu
         if buttons.just_pressed(buttons::START) {
@@ -273,6 +520,37 @@ pub fn update_players(
     }
 }
 
+/// Swings weapon in the direction faced.
+pub fn enter_attacking(
+    trigger: Trigger<EnterState<PlayerBehavior>>,
+    players: Query<(&Transform, &Facing, &Stats), With<Player>>,
+    mut commands: Commands,
+) {
+    if trigger.event().state != PlayerBehavior::Attacking { return };
+    let player_id = trigger.entity();
+    let Ok((transf, facing, stats)) = players.get(player_id) else { return };
+    let position = transf.translation + HURTBOX_OFFSET + facing.0.to_vec3() * ATTACK_REACH;
+    let damage = ATTACK_DAMAGE * stats.attack();
+    let hitbox = Hitbox::new(player_id, ATTACK_HITBOX_SIZE, damage, ATTACK_HITBOX_LIFETIME).with_knockback(ATTACK_KNOCKBACK);
+    spawn_hitbox(&mut commands, hitbox, position);
+}
+
+/// Starts dodging in the direction traveled, or the direction faced if standing still.
+pub fn enter_dodging(
+    trigger: Trigger<EnterState<PlayerBehavior>>,
+    mut players: Query<(&mut Player, &mut Stats, &Facing, &CharacterController)>,
+) {
+    if trigger.event().state != PlayerBehavior::Dodging { return };
+    let Ok((mut player, mut stats, facing, cc)) = players.get_mut(trigger.entity()) else { return };
+    player.dodge_dir = match cc.velocity.length_squared() > 0.0 {
+        true => cc.velocity.normalize(),
+        false => facing.0.to_vec3(),
+    };
+    player.dodge_traveled = 0.0;
+    player.dodge_cooldown = DODGE_DURATION + DODGE_COOLDOWN;
+    stats.invulnerable = stats.invulnerable.max(DODGE_IFRAMES);
+}
+
 fn xy_from_dpad(button_bits: u32) -> (i32, i32) {
     let (mut x, mut y) = (0, 0);
     if button_bits & buttons::LEFT != 0   { x -= 1; }
@@ -282,66 +560,56 @@ fn xy_from_dpad(button_bits: u32) -> (i32, i32) {
     (x, y)
 }
 
-fn card_dir_from_xy(x: i32, y: i32, prev_x: i32, prev_y: i32) -> Option<CardinalDirection> {
+fn card_dir_from_xy(x: i32, y: i32, prev_x: i32, prev_y: i32) -> Option<CompassDirection> {
     if x == 0 && y == 0 { return None }
     match (x, y) {
-        (0, 1)  => return Some(CardinalDirection::North),
-        (0, -1) => return Some(CardinalDirection::South),
-        (-1, 0) => return Some(CardinalDirection::West),
-        (1, 0)  => return Some(CardinalDirection::East),
+        (0, 1)  => return Some(CompassDirection::North),
+        (0, -1) => return Some(CompassDirection::South),
+        (-1, 0) => return Some(CompassDirection::West),
+        (1, 0)  => return Some(CompassDirection::East),
         _ => {}
     }
     match (x, prev_y, y) {
-        (1, 0, 1)   => return Some(CardinalDirection::North),   // RIGHT, recent UP
-        (1, 0, -1)  => return Some(CardinalDirection::South),   // RIGHT, recent DOWN
-        (-1, 0, 1)  => return Some(CardinalDirection::North),   // LEFT, recent UP
-        (-1, 0, -1) => return Some(CardinalDirection::South),   // LEFT, recent DOWN
+        (1, 0, 1)   => return Some(CompassDirection::North),   // RIGHT, recent UP
+        (1, 0, -1)  => return Some(CompassDirection::South),   // RIGHT, recent DOWN
+        (-1, 0, 1)  => return Some(CompassDirection::North),   // LEFT, recent UP
+        (-1, 0, -1) => return Some(CompassDirection::South),   // LEFT, recent DOWN
         _ => {}
     }
     match (y, prev_x, x) {
-        (1, 0, -1)  => return Some(CardinalDirection::West),   // UP, recent LEFT
-        (1, 0, 1)   => return Some(CardinalDirection::East),   // UP, recent RIGHT
-        (-1, 0, -1) => return Some(CardinalDirection::West),   // DOWN, recent LEFT
-        (-1, 0, 1)  => return Some(CardinalDirection::East),   // DOWN, recent RIGHT
+        (1, 0, -1)  => return Some(CompassDirection::West),   // UP, recent LEFT
+        (1, 0, 1)   => return Some(CompassDirection::East),   // UP, recent RIGHT
+        (-1, 0, -1) => return Some(CompassDirection::West),   // DOWN, recent LEFT
+        (-1, 0, 1)  => return Some(CompassDirection::East),   // DOWN, recent RIGHT
         _ => {}
     }
     None
 }
 
-pub fn update_character_controllers(mut controllers: Query<(&CharacterController, &mut Transform)>) {
-    for (cc, mut transf) in &mut controllers {
-        transf.translation += cc.velocity;
-    }
-}
+pub fn update_character_controllers(
+    mut controllers: Query<(&mut CharacterController, &mut Transform)>,
+    footprints: Query<(&Footprint, &GlobalTransform)>,
+) {
+    for (mut cc, mut transf) in &mut controllers {
+        transf.translation += cc.velocity + cc.step_external();
 
-pub fn update_player_animations(mut players: Query<(&Player, &mut AnimationState)>) {
-    for (player, mut player_anims) in &mut players {
-        
-        // Handle behavior / direction change
-        match (player.behavior_prev, player.behavior, player.card_dir) {
-            (PlayerBehavior::Idle, PlayerBehavior::Walking, direction) => {
-                player_anims.animation_idx = animations::WALK_BASE + direction as usize;
-                player_anims.frame_idx = 0;
-                player_anims.frame_elapsed = Duration::ZERO;
-            },
-            (PlayerBehavior::Walking, PlayerBehavior::Idle, direction) => {
-                player_anims.animation_idx = animations::IDLE_BASE + direction as usize;
-                player_anims.frame_idx = 0;
-                player_anims.frame_elapsed = Duration::ZERO;
-            },
-            (_, PlayerBehavior::Idle, direction) => {
-                player_anims.animation_idx = animations::IDLE_BASE + direction as usize;
-            },
-            (_, PlayerBehavior::Walking, direction) => {
-                player_anims.animation_idx = animations::WALK_BASE + direction as usize;
-            },
+        // Pushes character out of solid footprints
+        for (footprint, footprint_transf) in &footprints {
+            if let Some(pushed) = footprint.push_out(footprint_transf.translation(), transf.translation) {
+                transf.translation = pushed;
+            }
         }
     }
 }
 
-pub fn sync_players(mut players: Query<&mut Player>) {
-    for mut player in &mut players {
-        player.behavior_prev = player.behavior;
+/// Animation of each player behavior.
+pub fn player_animation(behavior: PlayerBehavior) -> StateAnimation {
+    match behavior {
+        PlayerBehavior::Idle => StateAnimation::looping(animations::IDLE_BASE),
+        PlayerBehavior::Walking => StateAnimation::looping(animations::WALK_BASE),
+        PlayerBehavior::Attacking => StateAnimation::once(animations::ATTACK_BASE),
+        PlayerBehavior::Dodging => StateAnimation::once(animations::DODGE_BASE),
+        PlayerBehavior::Swimming => StateAnimation::looping(animations::SWIM_BASE),
     }
 }
 
@@ -359,6 +627,20 @@ pub(crate) fn create_material(assets: &AssetServer, path: &'static str) -> Stand
     }
 }
 
+fn create_keyboard_mapping() -> KeyboardMapping {
+    KeyboardMapping::from([
+        (KeyCode::ArrowLeft,    buttons::LEFT),
+        (KeyCode::ArrowRight,   buttons::RIGHT),
+        (KeyCode::ArrowUp,      buttons::UP),
+        (KeyCode::ArrowDown,    buttons::DOWN),
+        (KeyCode::Enter,        buttons::START),
+        (KeyCode::Space,        buttons::CONFIRM),
+        (KeyCode::KeyX,         buttons::ATTACK),
+        (KeyCode::KeyC,         buttons::DODGE),
+        (KeyCode::KeyZ,         buttons::INTERACT),
+    ])
+}
+
 fn create_gamepad_mapping(gamepad: Gamepad) -> GamepadMapping {
     GamepadMapping::new(gamepad)
         .with_button(GamepadButtonType::DPadLeft, buttons::LEFT)
@@ -366,6 +648,10 @@ fn create_gamepad_mapping(gamepad: Gamepad) -> GamepadMapping {
         .with_button(GamepadButtonType::DPadUp, buttons::UP)
         .with_button(GamepadButtonType::DPadDown, buttons::DOWN)
         .with_button(GamepadButtonType::Start, buttons::START)
+        .with_button(GamepadButtonType::South, buttons::CONFIRM)
+        .with_button(GamepadButtonType::West, buttons::ATTACK)
+        .with_button(GamepadButtonType::East, buttons::DODGE)
+        .with_button(GamepadButtonType::North, buttons::INTERACT)
         .with_stick(StickType::Left, StickConfig { vstick_idx: sticks::LEFT, deadzones: Vec2::new(0.125, 0.125) })
 }
 
@@ -374,6 +660,8 @@ pub(crate) fn create_player_animations() -> AnimationSet {
     const SIZE: Vec2 = Vec2::new(64.0, 64.0);
     const STRIDE: Vec2 = Vec2::new(64.0, 0.0);
     const DURATION: Duration = Duration::from_millis(100);
+    const DODGE_FRAME_DURATION: Duration = Duration::from_millis(200);
+    const SWIM_FRAME_DURATION: Duration = Duration::from_millis(200);
     const ANCHOR: Anchor = Anchor::Custom(Vec2::new(0.0, -0.21));
 
     let idle_s = Animation::EMPTY.with_frames(1, Vec2::new(0.0, 0.0)*SIZE, SIZE, STRIDE, DURATION, ANCHOR);
@@ -386,9 +674,29 @@ pub(crate) fn create_player_animations() -> AnimationSet {
     let walk_e = Animation::EMPTY.with_frames(6, Vec2::new(0.0, 6.0)*SIZE, SIZE, STRIDE, DURATION, ANCHOR);
     let walk_w = Animation::EMPTY.with_frames(6, Vec2::new(0.0, 7.0)*SIZE, SIZE, STRIDE, DURATION, ANCHOR);
 
+    let attack_s = Animation::EMPTY.with_frames(3, Vec2::new(5.0, 0.0)*SIZE, SIZE, STRIDE, DURATION, ANCHOR);
+    let attack_n = Animation::EMPTY.with_frames(3, Vec2::new(5.0, 1.0)*SIZE, SIZE, STRIDE, DURATION, ANCHOR);
+    let attack_e = Animation::EMPTY.with_frames(3, Vec2::new(5.0, 2.0)*SIZE, SIZE, STRIDE, DURATION, ANCHOR);
+    let attack_w = Animation::EMPTY.with_frames(3, Vec2::new(5.0, 3.0)*SIZE, SIZE, STRIDE, DURATION, ANCHOR);
+
+    let dodge_s = Animation::EMPTY.with_frames(2, Vec2::new(6.0, 4.0)*SIZE, SIZE, STRIDE, DODGE_FRAME_DURATION, ANCHOR);
+    let dodge_n = Animation::EMPTY.with_frames(2, Vec2::new(6.0, 5.0)*SIZE, SIZE, STRIDE, DODGE_FRAME_DURATION, ANCHOR);
+    let dodge_e = Animation::EMPTY.with_frames(2, Vec2::new(6.0, 6.0)*SIZE, SIZE, STRIDE, DODGE_FRAME_DURATION, ANCHOR);
+    let dodge_w = Animation::EMPTY.with_frames(2, Vec2::new(6.0, 7.0)*SIZE, SIZE, STRIDE, DODGE_FRAME_DURATION, ANCHOR);
+
+    // Not a swim animation yet: the player sheets have no swim frames, so these are the walk rows slowed down.
+    // Replace with real swim rows once the art exists.
+    let swim_s = Animation::EMPTY.with_frames(6, Vec2::new(0.0, 4.0)*SIZE, SIZE, STRIDE, SWIM_FRAME_DURATION, ANCHOR);
+    let swim_n = Animation::EMPTY.with_frames(6, Vec2::new(0.0, 5.0)*SIZE, SIZE, STRIDE, SWIM_FRAME_DURATION, ANCHOR);
+    let swim_e = Animation::EMPTY.with_frames(6, Vec2::new(0.0, 6.0)*SIZE, SIZE, STRIDE, SWIM_FRAME_DURATION, ANCHOR);
+    let swim_w = Animation::EMPTY.with_frames(6, Vec2::new(0.0, 7.0)*SIZE, SIZE, STRIDE, SWIM_FRAME_DURATION, ANCHOR);
+
     AnimationSet::EMPTY.with_animations([
         idle_s, idle_n, idle_e, idle_w,
         walk_s, walk_n, walk_e, walk_w,
+        attack_s, attack_n, attack_e, attack_w,
+        dodge_s, dodge_n, dodge_e, dodge_w,
+        swim_s, swim_n, swim_e, swim_w,
     ])
 }
 
@@ -396,6 +704,9 @@ pub(crate) fn create_player_animations() -> AnimationSet {
 pub mod animations {
     pub const IDLE_BASE: usize = 0;
     pub const WALK_BASE: usize = 4;
+    pub const ATTACK_BASE: usize = 8;
+    pub const DODGE_BASE: usize = 12;
+    pub const SWIM_BASE: usize = 16;
 }
 
 /// Virtual player buttons
@@ -405,6 +716,10 @@ pub mod buttons {
     pub const UP: u32       = 1 << 2;
     pub const DOWN: u32     = 1 << 3;
     pub const START: u32    = 1 << 4;
+    pub const CONFIRM: u32  = 1 << 5;
+    pub const ATTACK: u32   = 1 << 6;
+    pub const DODGE: u32    = 1 << 7;
+    pub const INTERACT: u32 = 1 << 8;
 }
 
 /// Stick index
@@ -415,6 +730,12 @@ pub mod sticks {
 pub mod messages {
     use bevy::prelude::*;
 
+    use crate::input::InputDevice;
+
     #[derive(Event, Copy, Clone, PartialEq, Default, Debug)]
-    pub struct SpawnPlayer { pub position: Vec3 }
+    pub struct SpawnPlayer {
+        pub position: Vec3,
+        /// Device the player is controlled with.
+        pub device: InputDevice,
+    }
 }
\ No newline at end of file
@@ -0,0 +1,84 @@
+//! Saving and loading game progress.
+
+use std::fs;
+use std::path::PathBuf;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::*;
+use crate::flags::Flags;
+use messages::{LoadGame, SaveGame};
+
+const SAVE_DIR: &str = "saves";
+/// Slot saved to and loaded from in normal play.
+pub const GAME_SLOT: &str = "game";
+
+
+/// Everything written to a save file.
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct SaveData {
+    pub flags: Flags,
+}
+
+impl SaveData {
+    fn path(slot: &str) -> PathBuf {
+        PathBuf::from(SAVE_DIR).join(format!("{slot}.save.ron"))
+    }
+
+    pub fn write(&self, slot: &str) -> Result<(), SaveError> {
+        let ron = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        fs::create_dir_all(SAVE_DIR)?;
+        fs::write(Self::path(slot), ron)?;
+        Ok(())
+    }
+
+    pub fn read(slot: &str) -> Result<Self, SaveError> {
+        let ron = fs::read_to_string(Self::path(slot))?;
+        Ok(ron::from_str(&ron)?)
+    }
+}
+
+#[derive(Error, Debug)]
+#[error(transparent)]
+pub enum SaveError {
+    IOError(#[from] std::io::Error),
+    RonError(#[from] ron::error::SpannedError),
+    Serialize(#[from] ron::Error),
+}
+
+
+pub fn save_game(trigger: Trigger<SaveGame>, flags: Res<Flags>) {
+    let slot = &trigger.event().slot;
+    let save_data = SaveData { flags: flags.clone() };
+    match save_data.write(slot) {
+        Ok(_) => bevy::log::info!("Saved game to slot '{slot}'"),
+        Err(err) => bevy::log::error!("Failed to save game to slot '{slot}': {err}"),
+    }
+}
+
+pub fn load_game(trigger: Trigger<LoadGame>, mut commands: Commands) {
+    let slot = &trigger.event().slot;
+    match SaveData::read(slot) {
+        Ok(save_data) => {
+            commands.insert_resource(save_data.flags);
+            bevy::log::info!("Loaded game from slot '{slot}'");
+        },
+        Err(err) => bevy::log::error!("Failed to load game from slot '{slot}': {err}"),
+    }
+}
+
+
+pub mod messages {
+    use bevy::prelude::*;
+
+    /// Writes game progress to a save slot.
+    #[derive(Event, Clone, Eq, PartialEq, Debug)]
+    pub struct SaveGame {
+        pub slot: String,
+    }
+
+    /// Restores game progress from a save slot.
+    #[derive(Event, Clone, Eq, PartialEq, Debug)]
+    pub struct LoadGame {
+        pub slot: String,
+    }
+}
@@ -0,0 +1,38 @@
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::asset::io::Reader;
+use thiserror::*;
+
+use super::Script;
+
+/// Loads a [`Script`].
+#[derive(Default)]
+pub struct ScriptLoader;
+impl AssetLoader for ScriptLoader {
+
+    type Asset = Script;
+    type Settings = ();
+    type Error = ScriptLoadError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Script, ScriptLoadError> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes).await?;
+        let script: Script = ron::de::from_bytes(&bytes)?;
+        Ok(script)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["script.ron"]
+    }
+}
+
+#[derive(Error, Debug)]
+#[error(transparent)]
+pub enum ScriptLoadError {
+    IOError(#[from] std::io::Error),
+    RonError(#[from] ron::error::SpannedError),
+}
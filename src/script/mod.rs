@@ -0,0 +1,317 @@
+//! Cutscene scripts authored as RON assets.
+//!
+//! Example:
+//! ```ron
+//! Script(steps: [
+//!     Print("Hello"),
+//!     Move(target: Player, to: (64.0, 0.0, -32.0), secs: 1.0, ease: QuadOut),
+//!     Loop(times: 2, steps: [Wait(0.5), Print("Again")]),
+//! ])
+//! ```
+
+mod loader;
+
+pub use loader::*;
+
+use std::time::Duration;
+use bevy::prelude::*;
+use bevy::asset::LoadState;
+use bevy::utils::HashMap;
+use rand::Rng;
+use serde::Deserialize;
+use crate::act::StartEnvExt;
+use crate::action::composite::Parallel;
+use crate::action::tween::{Ease, TweenValue};
+use crate::flags::{Flag, Flags};
+use crate::action::{Action, ActionId, EndEnv, InnerActionQueue, RunEnv, RunStatus, StartEnv};
+use crate::map::{EntityType, SpawnEntity};
+use crate::messages::RunAction;
+use crate::player::buttons;
+use crate::EntityIndex;
+
+
+/// A sequence of [`Step`]s, compiled into actions when run.
+#[derive(Asset, TypePath, Deserialize, Clone, Default, Debug)]
+pub struct Script {
+    pub steps: Vec<Step>,
+}
+
+/// A single step in a [`Script`].
+#[derive(Deserialize, Clone, Debug)]
+pub enum Step {
+    /// Logs a message.
+    Print(String),
+    /// Shows a dialogue asset, waiting until it ends.
+    Dialogue(String),
+    /// Waits for a number of seconds.
+    Wait(f32),
+    /// Sets a flag.
+    SetFlag {
+        name: String,
+        value: Flag,
+    },
+    /// Adds to an integer flag.
+    AddFlag {
+        name: String,
+        amount: i64,
+    },
+    /// Waits until any player presses confirm.
+    WaitForConfirm,
+    /// Waits until an activator enters a trigger zone with the event name specified.
+    WaitForZone(String),
+    /// Runs a registered action, IE: "cutscene", or the path to another script.
+    Run(String),
+    /// Moves an entity to a position.
+    Move {
+        target: Target,
+        to: (f32, f32, f32),
+        secs: f32,
+        #[serde(default)]
+        ease: Ease,
+    },
+    /// Tweens a field of a reflected component.
+    Tween {
+        target: Target,
+        component: String,
+        path: String,
+        value: Value,
+        secs: f32,
+        #[serde(default)]
+        ease: Ease,
+    },
+    /// Fades the material of an entity to an alpha value.
+    Fade {
+        target: Target,
+        alpha: f32,
+        secs: f32,
+        #[serde(default)]
+        ease: Ease,
+    },
+    /// Spawns an entity the same way map objects are spawned.
+    Spawn {
+        entity: String,
+        position: (f32, f32, f32),
+        #[serde(default)]
+        size: (f32, f32, f32),
+    },
+    /// Runs one set of steps or another, depending on a condition.
+    Branch {
+        condition: Condition,
+        then: Vec<Step>,
+        #[serde(default)]
+        otherwise: Vec<Step>,
+    },
+    /// Runs steps a number of times.
+    Loop {
+        times: u32,
+        steps: Vec<Step>,
+    },
+    /// Runs several sequences of steps simultaneously, finishing when all have finished.
+    Parallel(Vec<Vec<Step>>),
+    /// Quits the script.
+    Quit,
+}
+
+/// Entity a [`Step`] operates on.
+#[derive(Deserialize, Clone, Eq, PartialEq, Debug)]
+pub enum Target {
+    /// First player to join.
+    Player,
+    /// First entity with the [`Name`] specified.
+    Named(String),
+    /// Entity running the script.
+    Queue,
+}
+
+impl Target {
+    fn resolve(&self, world: &mut World, queue_entity: Entity) -> Option<Entity> {
+        match self {
+            Target::Player => world.resource::<EntityIndex>().players.first().copied(),
+            Target::Named(name) => world
+                .query::<(Entity, &Name)>()
+                .iter(world)
+                .find(|(_, entity_name)| entity_name.as_str() == name)
+                .map(|(entity, _)| entity),
+            Target::Queue => Some(queue_entity),
+        }
+    }
+}
+
+/// Value a [`Step::Tween`] animates towards.
+#[derive(Deserialize, Copy, Clone, PartialEq, Debug)]
+pub enum Value {
+    F32(f32),
+    Vec2(f32, f32),
+    Vec3(f32, f32, f32),
+    Vec4(f32, f32, f32, f32),
+    Color(f32, f32, f32, f32),
+}
+
+impl From<Value> for TweenValue {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::F32(x)               => TweenValue::F32(x),
+            Value::Vec2(x, y)           => TweenValue::Vec2(Vec2::new(x, y)),
+            Value::Vec3(x, y, z)        => TweenValue::Vec3(Vec3::new(x, y, z)),
+            Value::Vec4(x, y, z, w)     => TweenValue::Vec4(Vec4::new(x, y, z, w)),
+            Value::Color(r, g, b, a)    => TweenValue::Color(Color::srgba(r, g, b, a)),
+        }
+    }
+}
+
+/// Condition of a [`Step::Branch`], evaluated when the branch is reached.
+#[derive(Deserialize, Clone, Debug)]
+pub enum Condition {
+    /// True with the probability specified.
+    Chance(f32),
+    /// True if the flag is set and truthy.
+    Flag(String),
+    /// True if the flag is set to the value specified.
+    FlagEquals {
+        name: String,
+        value: Flag,
+    },
+    /// True if the target exists.
+    Exists(Target),
+    /// True if the target is within a radius of a position.
+    Near {
+        target: Target,
+        position: (f32, f32, f32),
+        radius: f32,
+    },
+    Not(Box<Condition>),
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+}
+
+impl Condition {
+    fn evaluate(&self, world: &mut World, queue_entity: Entity) -> bool {
+        match self {
+            Condition::Chance(chance) => rand::thread_rng().gen::<f32>() < *chance,
+            Condition::Flag(name) => world.resource::<Flags>().is_set(name),
+            Condition::FlagEquals { name, value } => world.resource::<Flags>().get(name) == Some(value),
+            Condition::Exists(target) => target.resolve(world, queue_entity).is_some(),
+            Condition::Near { target, position, radius } => {
+                let Some(entity) = target.resolve(world, queue_entity) else { return false };
+                let Some(transf) = world.get::<Transform>(entity) else { return false };
+                transf.translation.distance(Vec3::from(*position)) <= *radius
+            },
+            Condition::Not(condition) => !condition.evaluate(world, queue_entity),
+            Condition::All(conditions) => conditions.iter().all(|cond| cond.evaluate(world, queue_entity)),
+            Condition::Any(conditions) => conditions.iter().any(|cond| cond.evaluate(world, queue_entity)),
+        }
+    }
+}
+
+
+/// Pushes the actions of each step.
+pub fn push_steps(env: &mut StartEnv, steps: &[Step]) {
+    for step in steps {
+        push_step(env, step.clone());
+    }
+}
+
+fn push_step(env: &mut StartEnv, step: Step) {
+    match step {
+        Step::Print(message) => env.start(move |_env| info!("{message}")),
+        Step::Dialogue(path) => env.dialogue(&path),
+        Step::Wait(secs) => env.wait_secs(secs),
+        Step::SetFlag { name, value } => env.set_flag(&name, value),
+        Step::AddFlag { name, amount } => env.add_flag(&name, amount),
+        Step::WaitForConfirm => env.wait_for_button(buttons::CONFIRM),
+        Step::WaitForZone(event) => env.wait_for_zone(&event),
+        Step::Run(action) => env.start(move |env| env.world.trigger(RunAction(ActionId::new(action)))),
+        Step::Move { target, to, secs, ease } => env.push(move |env: &mut StartEnv| {
+            let Some(entity) = target.resolve(env.world, env.entity) else { return };
+            env.move_to(entity, Vec3::from(to), secs, ease);
+        }),
+        Step::Tween { target, component, path, value, secs, ease } => env.push(move |env: &mut StartEnv| {
+            let Some(entity) = target.resolve(env.world, env.entity) else { return };
+            env.tween_field(entity, &component, &path, value, secs, ease);
+        }),
+        Step::Fade { target, alpha, secs, ease } => env.push(move |env: &mut StartEnv| {
+            let Some(entity) = target.resolve(env.world, env.entity) else { return };
+            env.fade_to(entity, alpha, secs, ease);
+        }),
+        Step::Spawn { entity, position, size } => match EntityType::parse(&entity) {
+            Some(entity_type) => env.start(move |env| env.world.trigger(SpawnEntity {
+                entity_type,
+                position: Vec3::from(position),
+                size: Vec3::from(size),
+                properties: HashMap::new(),
+            })),
+            None => bevy::log::warn!("Unexpected entity type '{entity}'"),
+        },
+        Step::Branch { condition, then, otherwise } => env.push(move |env: &mut StartEnv| {
+            match condition.evaluate(env.world, env.entity) {
+                true => push_steps(env, &then),
+                false => push_steps(env, &otherwise),
+            }
+        }),
+        Step::Loop { times, steps } => env.repeat(times, move |env: &mut StartEnv| push_steps(env, &steps)),
+        Step::Parallel(sequences) => {
+            let mut parallel = Parallel::new();
+            for steps in sequences {
+                parallel = parallel.with(move |env: &mut StartEnv| push_steps(env, &steps));
+            }
+            env.push(parallel);
+        },
+        Step::Quit => env.quit(),
+    }
+}
+
+
+/// Loads a [`Script`] asset, then runs its steps.
+/// Finishes immediately if the script fails to load.
+pub struct RunScript {
+    path: String,
+    handle: Option<Handle<Script>>,
+    steps: Option<InnerActionQueue>,
+}
+
+impl RunScript {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self { path: path.into(), handle: None, steps: None }
+    }
+}
+
+impl Action for RunScript {
+
+    fn start(&mut self, env: &mut StartEnv) {
+        let assets = env.world.resource::<AssetServer>();
+        self.handle = Some(assets.load(self.path.clone()));
+    }
+
+    fn run(&mut self, env: &mut RunEnv) -> RunStatus {
+        if self.steps.is_none() {
+            let handle = self.handle.as_ref().unwrap();
+            let scripts = env.world.resource::<Assets<Script>>();
+            match scripts.get(handle) {
+                Some(script) => {
+                    let steps = script.steps.clone();
+                    self.steps = Some(InnerActionQueue::from(move |env: &mut StartEnv| push_steps(env, &steps)));
+                },
+                None => {
+                    let assets = env.world.resource::<AssetServer>();
+                    if let Some(LoadState::Failed(err)) = assets.get_load_state(handle) {
+                        bevy::log::error!("Failed to load script '{}': {err}", self.path);
+                        return RunStatus::Finished { time_consumed: Duration::ZERO };
+                    }
+                    return RunStatus::Running;
+                },
+            }
+        }
+        let steps = self.steps.as_mut().unwrap();
+        steps.update(env.world, env.entity, env.delta)
+    }
+
+    fn name(&self) -> String {
+        format!("RunScript({})", self.path)
+    }
+
+    fn end(&mut self, env: &mut EndEnv) {
+        if let Some(mut steps) = self.steps.take() {
+            steps.end_all(env.world, env.entity);
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! Per-entity state machines with enter/exit hooks and per-state animations.
+//!
+//! Systems decide transitions by calling [`StateMachine::set`].
+//! [`apply_transitions`] then applies them, firing [`ExitState`] and [`EnterState`] at the entity.
+//! Enter/exit hooks are observers of those events.
+//! Update hooks are ordinary systems that match on [`StateMachine::current`].
+
+use std::fmt::Debug;
+use std::time::Duration;
+use bevy::prelude::*;
+use crate::animation::{AnimationMode, AnimationState};
+use crate::player::{Facing, FacingMode};
+use messages::{EnterState, ExitState};
+
+
+/// Trait alias for types usable as the states of a [`StateMachine`].
+/// Typically a fieldless enum.
+pub trait MachineState: Copy + Eq + Debug + Send + Sync + 'static {}
+impl<S: Copy + Eq + Debug + Send + Sync + 'static> MachineState for S {}
+
+/// Current state of an entity, and the time spent in it.
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct StateMachine<S: MachineState> {
+    current: S,
+    previous: S,
+    next: Option<S>,
+    elapsed: Duration,
+    just_entered: bool,
+}
+
+impl<S: MachineState> StateMachine<S> {
+
+    pub fn new(initial: S) -> Self {
+        Self {
+            current: initial,
+            previous: initial,
+            next: None,
+            elapsed: Duration::ZERO,
+            just_entered: true,
+        }
+    }
+
+    /// State the entity is in.
+    pub fn current(&self) -> S {
+        self.current
+    }
+
+    /// State the entity was in before the current one.
+    pub fn previous(&self) -> S {
+        self.previous
+    }
+
+    /// True if in the state specified.
+    pub fn is(&self, state: S) -> bool {
+        self.current == state
+    }
+
+    /// Time spent in the current state, as of the last [`apply_transitions`].
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// True if the current state was entered on this frame.
+    pub fn just_entered(&self) -> bool {
+        self.just_entered
+    }
+
+    /// Schedules a transition, applied by [`apply_transitions`].
+    /// Setting the current state does nothing.
+    /// Use [`restart`](Self::restart) to re-enter it.
+    pub fn set(&mut self, next: S) {
+        self.next = match next == self.current {
+            true => None,
+            false => Some(next),
+        };
+    }
+
+    /// Schedules a transition out of and back into the current state.
+    pub fn restart(&mut self) {
+        self.next = Some(self.current);
+    }
+}
+
+impl<S: MachineState + Default> Default for StateMachine<S> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+/// Maps the states of a [`StateMachine`] to animations.
+#[derive(Component, Copy, Clone, Debug)]
+pub struct StateAnimations<S: MachineState>(pub fn(S) -> StateAnimation);
+
+/// Animation played while in a particular state.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct StateAnimation {
+    /// Index of the animation, or the first of its directional variants.
+    pub animation_idx: usize,
+    pub mode: AnimationMode,
+    /// If true, the offset of the entity's [`Facing`] direction is added to the index.
+    /// Offsets come from the entity's [`FacingMode`], or [`FacingMode::FOUR`] if it has none.
+    pub directional: bool,
+}
+
+impl StateAnimation {
+    /// A looping animation with a variant for each direction.
+    pub fn looping(animation_idx: usize) -> Self {
+        Self { animation_idx, mode: AnimationMode::Loop, directional: true }
+    }
+
+    /// An animation that plays once, with a variant for each direction.
+    pub fn once(animation_idx: usize) -> Self {
+        Self { animation_idx, mode: AnimationMode::Play, directional: true }
+    }
+}
+
+
+/// Applies scheduled transitions, firing [`ExitState`] then [`EnterState`] at each entity that transitioned.
+/// Accumulates time spent in states otherwise.
+pub fn apply_transitions<S: MachineState>(
+    mut machines: Query<(Entity, &mut StateMachine<S>)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut machine) in &mut machines {
+        let Some(next) = machine.next.take() else {
+            machine.elapsed += time.delta();
+            machine.just_entered = false;
+            continue;
+        };
+        let prev = machine.current;
+        machine.previous = prev;
+        machine.current = next;
+        machine.elapsed = Duration::ZERO;
+        machine.just_entered = true;
+        commands.trigger_targets(ExitState { state: prev }, entity);
+        commands.trigger_targets(EnterState { state: next }, entity);
+    }
+}
+
+/// Plays the animation of each entity's current state.
+/// Restarts it when a state is entered.
+pub fn update_state_animations<S: MachineState>(
+    mut entities: Query<(&StateMachine<S>, &StateAnimations<S>, Option<&Facing>, Option<&FacingMode>, &mut AnimationState)>,
+) {
+    for (machine, animations, facing, facing_mode, mut anim_state) in &mut entities {
+        let animation = (animations.0)(machine.current());
+        let animation_idx = match (animation.directional, facing) {
+            (true, Some(facing)) => animation.animation_idx + facing_mode.copied().unwrap_or_default().offset(facing.0),
+            _ => animation.animation_idx,
+        };
+        if anim_state.animation_idx != animation_idx {
+            anim_state.animation_idx = animation_idx;
+        }
+        if machine.just_entered() {
+            anim_state.frame_idx = 0;
+            anim_state.frame_elapsed = Duration::ZERO;
+            anim_state.mode = animation.mode;
+        }
+    }
+}
+
+
+pub mod messages {
+    use bevy::prelude::*;
+    use super::MachineState;
+
+    /// Fired when an entity enters a state.
+    /// Targets the entity.
+    #[derive(Event, Copy, Clone, Eq, PartialEq, Debug)]
+    pub struct EnterState<S: MachineState> {
+        pub state: S,
+    }
+
+    /// Fired when an entity exits a state, just before [`EnterState`].
+    /// Targets the entity.
+    #[derive(Event, Copy, Clone, Eq, PartialEq, Debug)]
+    pub struct ExitState<S: MachineState> {
+        pub state: S,
+    }
+}
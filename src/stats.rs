@@ -0,0 +1,257 @@
+//! Health, stats and death of characters.
+
+use std::time::Duration;
+use bevy::prelude::*;
+use crate::messages::Damaged;
+use crate::player::CharacterController;
+use messages::{Died, Heal};
+
+/// Decay of knockback impulses, on top of friction.
+const KNOCKBACK_DECAY: f32 = 0.9;
+
+
+/// A stat that can be modified by a [`Modifier`].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Stat {
+    MaxHp,
+    Attack,
+    Defense,
+    Speed,
+}
+
+/// How a [`Modifier`] changes a stat.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ModifierKind {
+    /// Adds to the base value.
+    Add(f32),
+    /// Multiplies the value, after all additions are applied.
+    Multiply(f32),
+}
+
+/// Temporarily or permanently changes a stat.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Modifier {
+    pub stat: Stat,
+    pub kind: ModifierKind,
+    /// Time remaining before the modifier is removed. Permanent if [`None`].
+    pub duration: Option<Duration>,
+}
+
+/// Base values of stats before modifiers are applied.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct BaseStats {
+    pub max_hp: f32,
+    pub attack: f32,
+    pub defense: f32,
+    pub speed: f32,
+}
+
+impl Default for BaseStats {
+    fn default() -> Self {
+        Self {
+            max_hp: 10.0,
+            attack: 1.0,
+            defense: 0.0,
+            speed: 2.0,
+        }
+    }
+}
+
+/// Health and stats of a character.
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct Stats {
+    pub hp: f32,
+    pub base: BaseStats,
+    pub modifiers: Vec<Modifier>,
+    /// How long the character is invulnerable for after taking damage.
+    pub iframes: Duration,
+    /// Time remaining before the character can take damage again.
+    pub invulnerable: Duration,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self::new(BaseStats::default())
+    }
+}
+
+impl Stats {
+
+    /// Stats at full health.
+    pub fn new(base: BaseStats) -> Self {
+        Self {
+            hp: base.max_hp,
+            base,
+            modifiers: vec![],
+            iframes: Duration::ZERO,
+            invulnerable: Duration::ZERO,
+        }
+    }
+
+    pub fn with_iframes(mut self, iframes: Duration) -> Self {
+        self.iframes = iframes;
+        self
+    }
+
+    /// Value of a stat with all modifiers applied.
+    pub fn get(&self, stat: Stat) -> f32 {
+        let base = match stat {
+            Stat::MaxHp => self.base.max_hp,
+            Stat::Attack => self.base.attack,
+            Stat::Defense => self.base.defense,
+            Stat::Speed => self.base.speed,
+        };
+        let (mut add, mut multiply) = (0.0, 1.0);
+        for modifier in self.modifiers.iter().filter(|modifier| modifier.stat == stat) {
+            match modifier.kind {
+                ModifierKind::Add(value) => add += value,
+                ModifierKind::Multiply(value) => multiply *= value,
+            }
+        }
+        (base + add) * multiply
+    }
+
+    pub fn max_hp(&self) -> f32 { self.get(Stat::MaxHp) }
+    pub fn attack(&self) -> f32 { self.get(Stat::Attack) }
+    pub fn defense(&self) -> f32 { self.get(Stat::Defense) }
+    pub fn speed(&self) -> f32 { self.get(Stat::Speed) }
+
+    pub fn add_modifier(&mut self, modifier: Modifier) {
+        self.modifiers.push(modifier);
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.hp <= 0.0
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        !self.invulnerable.is_zero()
+    }
+
+    /// Reduces HP by an amount minus defense, always dealing at least 1.
+    /// Does nothing if dead or invulnerable.
+    /// Returns true if this killed the character.
+    pub fn damage(&mut self, amount: f32) -> bool {
+        if self.is_dead() || self.is_invulnerable() { return false };
+        let amount = (amount - self.defense()).max(1.0);
+        self.hp = (self.hp - amount).max(0.0);
+        self.invulnerable = self.iframes;
+        self.is_dead()
+    }
+
+    /// Restores HP, up to max HP.
+    /// Does nothing if dead.
+    pub fn heal(&mut self, amount: f32) {
+        if self.is_dead() { return };
+        self.hp = (self.hp + amount).min(self.max_hp());
+    }
+
+    /// Restores HP to max HP, and removes temporary modifiers.
+    pub fn revive(&mut self) {
+        self.modifiers.retain(|modifier| modifier.duration.is_none());
+        self.hp = self.max_hp();
+    }
+}
+
+/// Where a character returns to when it dies.
+/// Characters with stats, but without a respawn point, are despawned when they die.
+#[derive(Component, Copy, Clone, PartialEq, Default, Debug)]
+pub struct RespawnPoint(pub Vec3);
+
+
+/// Counts down invulnerability and modifier durations.
+/// Keeps HP within max HP.
+/// Only writes to stats while something is counting down, so that [`Changed<Stats>`] stays meaningful.
+pub fn update_stats(mut characters: Query<&mut Stats>, time: Res<Time>) {
+    let delta = time.delta();
+    for mut stats in &mut characters {
+        if stats.is_invulnerable() {
+            stats.invulnerable = stats.invulnerable.saturating_sub(delta);
+        }
+        if stats.modifiers.iter().all(|modifier| modifier.duration.is_none()) { continue };
+        let modifier_count = stats.modifiers.len();
+        stats.modifiers.retain_mut(|modifier| match &mut modifier.duration {
+            Some(duration) => {
+                *duration = duration.saturating_sub(delta);
+                !duration.is_zero()
+            },
+            None => true,
+        });
+        if stats.modifiers.len() != modifier_count {
+            stats.hp = stats.hp.min(stats.max_hp());
+        }
+    }
+}
+
+/// Syncs top speed with the speed stat when stats change.
+pub fn sync_top_speed(mut characters: Query<(&Stats, &mut CharacterController), Changed<Stats>>) {
+    for (stats, mut cc) in &mut characters {
+        let speed = stats.speed();
+        if cc.top_speed != speed {
+            cc.top_speed = speed;
+        }
+    }
+}
+
+/// Applies damage and knockback from hitboxes, firing [`Died`] when it kills.
+/// Knockback is ignored while invulnerable.
+pub fn apply_damage(
+    trigger: Trigger<Damaged>,
+    mut characters: Query<(&mut Stats, Option<&mut CharacterController>)>,
+    mut commands: Commands,
+) {
+    let target = trigger.entity();
+    let damaged = trigger.event();
+    let Ok((mut stats, cc)) = characters.get_mut(target) else { return };
+    if stats.is_dead() || stats.is_invulnerable() { return };
+    if stats.damage(damaged.amount) {
+        commands.trigger_targets(Died, target);
+    }
+    if let Some(mut cc) = cc {
+        let direction = Vec3::new(damaged.direction.x, 0.0, damaged.direction.z).normalize_or_zero();
+        cc.apply_impulse(direction * damaged.knockback, KNOCKBACK_DECAY);
+    }
+}
+
+pub fn apply_heal(trigger: Trigger<Heal>, mut characters: Query<&mut Stats>) {
+    let Ok(mut stats) = characters.get_mut(trigger.entity()) else { return };
+    stats.heal(trigger.event().amount);
+}
+
+/// Respawns characters with a [`RespawnPoint`], and despawns the rest.
+/// There is no game over: players always have a respawn point, so they respawn like any other character.
+pub fn handle_death(
+    trigger: Trigger<Died>,
+    mut characters: Query<(&mut Stats, &mut Transform, Option<&RespawnPoint>, Option<&mut CharacterController>)>,
+    mut commands: Commands,
+) {
+    let entity = trigger.entity();
+    let Ok((mut stats, mut transf, respawn_point, cc)) = characters.get_mut(entity) else { return };
+    let Some(respawn_point) = respawn_point else {
+        commands.entity(entity).despawn_recursive();
+        return;
+    };
+    stats.revive();
+    stats.invulnerable = stats.iframes;
+    transf.translation = respawn_point.0;
+    if let Some(mut cc) = cc {
+        cc.velocity = Vec3::ZERO;
+        cc.clear_external();
+    }
+}
+
+
+pub mod messages {
+    use bevy::prelude::*;
+
+    /// Restores HP of the targeted character.
+    #[derive(Event, Copy, Clone, PartialEq, Debug)]
+    pub struct Heal {
+        pub amount: f32,
+    }
+
+    /// Fired when a character's HP reaches zero.
+    /// Targets the character that died.
+    #[derive(Event, Copy, Clone, Eq, PartialEq, Default, Debug)]
+    pub struct Died;
+}
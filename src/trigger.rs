@@ -0,0 +1,141 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use smallvec::SmallVec;
+use tiled_parser::PropertyValue;
+use crate::action::ActionId;
+use crate::area::AreaLocal;
+use crate::flags::Flags;
+use crate::messages::RunAction;
+use messages::{ZoneEntered, ZoneExited};
+
+
+/// A designer-placed region that fires [`ZoneEntered`] and [`ZoneExited`] when
+/// a [`ZoneActivator`] enters or exits it.
+/// Both events target the zone's entity.
+#[derive(Component, Clone, PartialEq, Default, Debug)]
+pub struct TriggerZone {
+    /// Size of the zone's AABB, centered on its translation.
+    pub size: Vec3,
+    /// Name included in events fired by this zone.
+    pub event: Option<String>,
+    /// Action to run when an activator enters the zone.
+    pub action: Option<ActionId>,
+    /// If true, zone despawns after it is entered for the first time.
+    pub once: bool,
+    /// Flag to set when an activator enters the zone.
+    pub set_flag: Option<String>,
+    /// Activators currently inside of the zone.
+    pub occupants: SmallVec<[Entity; 4]>,
+}
+
+impl TriggerZone {
+    fn contains(&self, zone_pos: Vec3, point: Vec3) -> bool {
+        let hsize = self.size / 2.0;
+        let (min, max) = (zone_pos - hsize, zone_pos + hsize);
+        point.x >= min.x && point.x <= max.x &&
+        point.y >= min.y && point.y <= max.y &&
+        point.z >= min.z && point.z <= max.z
+    }
+}
+
+/// Marks an [`Entity`] as being able to enter and exit [`TriggerZone`]s.
+#[derive(Component, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct ZoneActivator;
+
+pub fn spawn_trigger_zone(
+    commands: &mut Commands,
+    position: Vec3,
+    size: Vec3,
+    properties: &HashMap<String, PropertyValue>,
+) {
+    let mut zone = TriggerZone { size, ..default() };
+    for (prop_name, prop_value) in properties {
+        match (prop_name.as_str(), prop_value) {
+            ("event", PropertyValue::String(event))     => zone.event = Some(event.clone()),
+            ("action", PropertyValue::String(action))   => zone.action = Some(ActionId::new(action)),
+            ("once", PropertyValue::Bool(once))         => zone.once = *once,
+            ("set_flag", PropertyValue::String(flag))   => zone.set_flag = Some(flag.clone()),
+            ("event", _) => panic!("Property 'event' not a string"),
+            ("action", _) => panic!("Property 'action' not a string"),
+            ("once", _) => panic!("Property 'once' not a bool"),
+            ("set_flag", _) => panic!("Property 'set_flag' not a string"),
+            _ => {}
+        }
+    }
+    commands.spawn((
+        Name::new("trigger"),
+        zone,
+        SpatialBundle::from_transform(Transform::from_translation(position)),
+        AreaLocal { size: Vec2::new(size.x, size.y + size.z) },
+    ));
+}
+
+/// Detects activators entering and exiting trigger zones.
+pub fn update_trigger_zones(
+    mut zones: Query<(Entity, &mut TriggerZone, &Transform)>,
+    activators: Query<(Entity, &Transform), With<ZoneActivator>>,
+    mut flags: ResMut<Flags>,
+    mut commands: Commands,
+) {
+    for (zone_id, mut zone, zone_transf) in &mut zones {
+        let zone_pos = zone_transf.translation;
+
+        // Handles activators that left the zone, or no longer exist.
+        let mut occupant_idx = 0;
+        while occupant_idx < zone.occupants.len() {
+            let occupant_id = zone.occupants[occupant_idx];
+            let still_inside = match activators.get(occupant_id) {
+                Ok((_, occupant_transf)) => zone.contains(zone_pos, occupant_transf.translation),
+                Err(_) => false,
+            };
+            if still_inside {
+                occupant_idx += 1;
+                continue;
+            }
+            zone.occupants.swap_remove(occupant_idx);
+            let event = zone.event.clone();
+            commands.trigger_targets(ZoneExited { zone: zone_id, entity: occupant_id, event }, zone_id);
+        }
+
+        // Handles activators that entered the zone.
+        for (activator_id, activator_transf) in &activators {
+            if zone.occupants.contains(&activator_id) { continue };
+            if !zone.contains(zone_pos, activator_transf.translation) { continue };
+            zone.occupants.push(activator_id);
+            let event = zone.event.clone();
+            commands.trigger_targets(ZoneEntered { zone: zone_id, entity: activator_id, event }, zone_id);
+            if let Some(action) = &zone.action {
+                commands.trigger(RunAction(action.clone()));
+            }
+            if let Some(flag) = &zone.set_flag {
+                flags.set(flag.clone(), true);
+            }
+            if zone.once {
+                commands.entity(zone_id).despawn_recursive();
+                break;
+            }
+        }
+    }
+}
+
+
+pub mod messages {
+    use bevy::prelude::*;
+
+    /// Fired when a [`ZoneActivator`](super::ZoneActivator) enters a [`TriggerZone`](super::TriggerZone).
+    #[derive(Event, Clone, Eq, PartialEq, Debug)]
+    pub struct ZoneEntered {
+        pub zone: Entity,
+        pub entity: Entity,
+        pub event: Option<String>,
+    }
+
+    /// Fired when a [`ZoneActivator`](super::ZoneActivator) exits a [`TriggerZone`](super::TriggerZone),
+    /// or is despawned while inside of it.
+    #[derive(Event, Clone, Eq, PartialEq, Debug)]
+    pub struct ZoneExited {
+        pub zone: Entity,
+        pub entity: Entity,
+        pub event: Option<String>,
+    }
+}
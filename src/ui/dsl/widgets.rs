@@ -72,6 +72,24 @@ impl TextW {
     }
 }
 
+#[derive(Default, Debug, Deref, DerefMut)]
+pub struct ImageW(pub ImageBundle);
+impl ImageW {
+    pub fn new(image: &Handle<Image>) -> Self {
+        Self(ImageBundle {
+            image: UiImage { texture: image.clone(), ..default() },
+            ..default()
+        })
+    }
+    pub fn spawn(self, dsl: &mut Dsl) {
+        dsl.spawn(self.0);
+    }
+    pub fn class(mut self, class: impl Fn(&mut Self)) -> Self {
+        class(&mut self);
+        self
+    }
+}
+
 #[derive(Default, Debug)]
 pub struct SlicedImageW {
     pub image: ImageBundle,
@@ -4,6 +4,9 @@ pub use dsl::*;
 use bevy::prelude::*;
 use messages::{MenuEvent, ToggleEquipmentMenu};
 use crate::equipment::{Equippable, Hair, HairKind, Outfit};
+use crate::messages::{LoadGame, SaveGame};
+use crate::save::GAME_SLOT;
+use crate::PauseStates;
 
 const FONT_SIZE: f32 = 14.0;
 const ICON_SIZE: UVec2 = UVec2::new(32, 28);
@@ -21,14 +24,17 @@ pub struct EquipmentMenu {
 pub fn toggle_equipment_menu(
     _trigger: Trigger<ToggleEquipmentMenu>,
     equipment_menu: Option<Res<EquipmentMenu>>,
+    mut next_pause_state: ResMut<NextState<PauseStates>>,
     mut commands: Commands,
 ) {
     match equipment_menu {
         Some(equipment_menu) => {
             commands.entity(equipment_menu.entity).despawn_recursive();
             commands.remove_resource::<EquipmentMenu>();
+            next_pause_state.set(PauseStates::Running);
         },
         None => {
+            next_pause_state.set(PauseStates::Paused);
             let fullscreen_node = create_node(c_fullscreen);
             let entity = commands.spawn(fullscreen_node).id();
             commands.insert_resource(EquipmentMenu {
@@ -55,8 +61,20 @@ pub fn toggle_equipment_menu(
 pub fn handle_menu_events(
     trigger: Trigger<MenuEvent>,
     equipment_menu: Option<ResMut<EquipmentMenu>>,
+    mut commands: Commands,
 ) {
     let Some(mut menu) = equipment_menu else { return };
+    match trigger.event() {
+        MenuEvent::Save => {
+            commands.trigger(SaveGame { slot: GAME_SLOT.into() });
+            return;
+        },
+        MenuEvent::Load => {
+            commands.trigger(LoadGame { slot: GAME_SLOT.into() });
+            return;
+        },
+        _ => {}
+    }
     let (hair_sel, hair_len) = (menu.hair_selected, menu.hair_list.len());
     let (outfit_sel, outfit_len) = (menu.outfit_selected, menu.outfit_list.len());
     if hair_len == 0 { return };
@@ -65,6 +83,7 @@ pub fn handle_menu_events(
         MenuEvent::PreviousHair     => menu.hair_selected = (hair_sel + hair_len - 1).rem_euclid(hair_len),
         MenuEvent::NextOutfit       => menu.outfit_selected = (outfit_sel + 1).rem_euclid(outfit_len),
         MenuEvent::PreviousOutfit   => menu.outfit_selected = (outfit_sel + outfit_len - 1).rem_euclid(outfit_len),
+        MenuEvent::Save | MenuEvent::Load => {}
     }
 }
 
@@ -92,40 +111,49 @@ pub fn render_equipment_menu(
     let sel_outfit_image = assets.load::<Image>(sel_outfit_info.image);
     let outfit_region = icon_region(16, 16);
 
-    SlicedImageW::new(&frame).class(c_frame).begin(d);
-        TextW::new("Equipment").class(c_text).class(c_title).font(&font).font_size(FONT_SIZE).spawn(d);
+    SlicedImageW::new(&frame).class(c_frame).class(c_menu_frame).begin(d);
+        TextW::new("Equipment").class(c_text).class(c_centered).class(c_title).font(&font).font_size(FONT_SIZE).spawn(d);
         NodeW::cls(c_body).begin(d);
         
             NodeW::cls(c_row).begin(d);
                 TextButtonW::new("<").text_class(c_next_prev).font(&font).font_size(FONT_SIZE).on_press(MenuEvent::PreviousHair).spawn(d);
                 SlicedImageW::new(&sel_hair_image).region(hair_region, UVec2::new(512, 512), &mut atluses).class(c_icon).spawn(d);
                 TextButtonW::new(">").text_class(c_next_prev).font(&font).font_size(FONT_SIZE).on_press(MenuEvent::NextHair).spawn(d);
-                TextW::new(sel_hair_name).class(c_text).font(&font).font_size(FONT_SIZE).spawn(d);
+                TextW::new(sel_hair_name).class(c_text).class(c_centered).font(&font).font_size(FONT_SIZE).spawn(d);
             NodeW::end(d);
 
             NodeW::cls(c_row).begin(d);
                 TextButtonW::new("<").text_class(c_next_prev).font(&font).font_size(FONT_SIZE).on_press(MenuEvent::PreviousOutfit).spawn(d);
                 SlicedImageW::new(&sel_outfit_image).region(outfit_region, UVec2::new(512, 512), &mut atluses).class(c_icon).spawn(d);
                 TextButtonW::new(">").text_class(c_next_prev).font(&font).font_size(FONT_SIZE).on_press(MenuEvent::NextOutfit).spawn(d);
-                TextW::new(sel_outfit_name.clone()).class(c_text).font(&font).font_size(FONT_SIZE).spawn(d);
+                TextW::new(sel_outfit_name.clone()).class(c_text).class(c_centered).font(&font).font_size(FONT_SIZE).spawn(d);
+            NodeW::end(d);
+
+            NodeW::cls(c_row).begin(d);
+                TextButtonW::new("Save").text_class(c_next_prev).font(&font).font_size(FONT_SIZE).on_press(MenuEvent::Save).spawn(d);
+                TextButtonW::new("Load").text_class(c_next_prev).font(&font).font_size(FONT_SIZE).on_press(MenuEvent::Load).spawn(d);
             NodeW::end(d);
 
         NodeW::end(d);
     SlicedImageW::end(d);
 }
 
-fn c_frame(image: &mut SlicedImageW) {
-    let style = &mut image.image.style;
+/// Paper frame shared by the game's panels.
+pub(crate) fn c_frame(image: &mut SlicedImageW) {
     let slicer = &mut image.slicer;
+    slicer.border = BorderRect { left: 20.0, right: 20.0, top: 20.0, bottom: 20.0 };
+    slicer.center_scale_mode = SliceScaleMode::Tile { stretch_value: 1.0 };
+    slicer.sides_scale_mode = SliceScaleMode::Tile { stretch_value: 1.0 };
+}
+
+fn c_menu_frame(image: &mut SlicedImageW) {
+    let style = &mut image.image.style;
     style.width = Val::Px(250.0);
-    style.height = Val::Px(150.0);
+    style.height = Val::Px(170.0);
     style.flex_direction = FlexDirection::Column;
     style.justify_content = JustifyContent::Stretch;
     style.align_items = AlignItems::Stretch;
     style.padding = UiRect::top(Val::Px(100.0));
-    slicer.border = BorderRect { left: 20.0, right: 20.0, top: 20.0, bottom: 20.0 };
-    slicer.center_scale_mode = SliceScaleMode::Tile { stretch_value: 1.0 };
-    slicer.sides_scale_mode = SliceScaleMode::Tile { stretch_value: 1.0 };    
 }
 
 fn c_icon(image: &mut SlicedImageW) {
@@ -153,14 +181,18 @@ fn c_fullscreen(node: &mut NodeBundle) {
     node.style.align_items = AlignItems::Center;
 }
 
-fn c_text(text: &mut TextW) {
-    text.text.justify = JustifyText::Center;
+/// Ink color of text drawn on a [`c_frame`].
+pub(crate) fn c_text(text: &mut TextW) {
     text.set_color(Srgba::from_u8_array([64, 32, 0, 255]).into());
 }
 
+fn c_centered(text: &mut TextW) {
+    text.text.justify = JustifyText::Center;
+}
+
 fn c_next_prev(text: &mut TextW) {
+    c_text(text);
     text.style.margin = UiRect::horizontal(Val::Px(5.0));
-    text.set_color(Srgba::from_u8_array([64, 32, 0, 255]).into());
 }
 
 fn c_title(text: &mut TextW) {
@@ -168,7 +200,7 @@ fn c_title(text: &mut TextW) {
 }
 
 
-fn create_node(class: impl Fn(&mut NodeBundle)) -> NodeBundle {
+pub(crate) fn create_node(class: impl Fn(&mut NodeBundle)) -> NodeBundle {
     let mut bundle = NodeBundle::default();
     class(&mut bundle);
     bundle
@@ -188,7 +220,8 @@ pub mod messages {
 
     use bevy::prelude::*;
 
-    /// Shows / hides equipment menu
+    /// Shows / hides equipment menu.
+    /// The game is paused while it is shown.
     #[derive(Event, Copy, Clone, Eq, PartialEq, Default, Debug)]
     pub struct ToggleEquipmentMenu;
 
@@ -199,5 +232,9 @@ pub mod messages {
         PreviousHair,
         NextOutfit,
         PreviousOutfit,
+        /// Saves to [`GAME_SLOT`](crate::save::GAME_SLOT).
+        Save,
+        /// Loads from [`GAME_SLOT`](crate::save::GAME_SLOT).
+        Load,
     }
 }
\ No newline at end of file
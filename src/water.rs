@@ -0,0 +1,186 @@
+//! Wading, swimming, splashes and things that float or sink.
+
+use std::time::Duration;
+use bevy::prelude::*;
+use crate::common::CommonAssets;
+use crate::player::CharacterController;
+
+/// Water at least this deep must be swum in, rather than waded through.
+pub const DEEP_WATER_DEPTH: f32 = 12.0;
+const SPLASH_LIFETIME: Duration = Duration::from_millis(400);
+const SPLASH_SIZE: f32 = 8.0;
+const SPLASH_GROWTH: f32 = 3.0;
+const SPLASH_ALPHA: f32 = 0.8;
+const BOB_AMPLITUDE: f32 = 1.0;
+const BOB_SPEED: f32 = 3.0;
+const SINK_SPEED: f32 = 8.0;
+/// How far above a water's surface entities still touch it. IE: floating things bobbing.
+const SURFACE_CONTACT: f32 = BOB_AMPLITUDE + 1.0;
+
+
+/// How deep a body of [`Water`] is.
+#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Debug)]
+pub enum WaterDepth {
+    /// Slows characters down.
+    Shallow,
+    /// Characters swim, and things float or sink.
+    Deep,
+}
+
+impl WaterDepth {
+    pub fn from_depth(depth: f32) -> Self {
+        match depth >= DEEP_WATER_DEPTH {
+            true => Self::Deep,
+            false => Self::Shallow,
+        }
+    }
+}
+
+/// A body of water.
+#[derive(Component, Copy, Clone, PartialEq, Debug)]
+pub struct Water {
+    /// Size of the water.
+    /// x and z are centered on its translation, whose height is the surface. y is the depth below the surface.
+    pub size: Vec3,
+    pub depth: WaterDepth,
+}
+
+impl Water {
+    /// True if a point is within the water's footprint, no higher than just above its surface,
+    /// and no lower than its depth below its surface.
+    fn contains(&self, surface_pos: Vec3, point: Vec3) -> bool {
+        let dist = (point - surface_pos).abs();
+        let max_dist = self.size / 2.0;
+        let height = point.y - surface_pos.y;
+        dist.x <= max_dist.x && dist.z <= max_dist.z && height <= SURFACE_CONTACT && height >= -self.size.y
+    }
+}
+
+/// Water an entity is in, if any.
+#[derive(Component, Copy, Clone, PartialEq, Default, Debug)]
+pub struct WaterContact {
+    /// Depth of the deepest water the entity is in.
+    pub depth: Option<WaterDepth>,
+    /// Height of that water's surface.
+    pub surface: f32,
+}
+
+/// What happens to an entity in deep water.
+#[derive(Component, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Buoyancy {
+    /// Bobs on the surface.
+    Floats,
+    /// Sinks, and despawns once at the bottom.
+    Sinks,
+}
+
+/// A ripple that grows and fades where something entered or left water.
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct Splash {
+    pub material: Handle<StandardMaterial>,
+    pub elapsed: Duration,
+}
+
+/// Spawns a [`Splash`] on the surface of water.
+pub fn spawn_splash(
+    commands: &mut Commands,
+    position: Vec3,
+    common_assets: &CommonAssets,
+    materials: &mut Assets<StandardMaterial>,
+) {
+    let material = materials.add(StandardMaterial {
+        base_color: Color::srgba(1.0, 1.0, 1.0, SPLASH_ALPHA),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    });
+    commands.spawn((
+        Name::new("splash"),
+        Splash { material: material.clone(), elapsed: Duration::ZERO },
+        PbrBundle {
+            mesh: common_assets.meshes.plane.clone(),
+            material,
+            transform: Transform::from_translation(position).with_scale(Vec3::splat(SPLASH_SIZE)),
+            ..default()
+        },
+    ));
+}
+
+/// Finds the water each entity is in.
+/// Splashes when entities enter or leave water, or go from shallow to deep.
+/// Syncs character controllers with the water they are in.
+pub fn update_water_contacts(
+    mut contacts: Query<(&GlobalTransform, &mut WaterContact, Option<&mut CharacterController>)>,
+    waters: Query<(&Water, &GlobalTransform)>,
+    common_assets: Res<CommonAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+) {
+    for (transf, mut contact, cc) in &mut contacts {
+        let position = transf.translation();
+        let deepest = waters.iter()
+            .filter(|(water, water_transf)| water.contains(water_transf.translation(), position))
+            .max_by_key(|(water, _)| water.depth)
+            .map(|(water, water_transf)| (water.depth, water_transf.translation().y));
+        let depth = deepest.map(|(depth, _)| depth);
+        if depth != contact.depth {
+            let surface = deepest.map(|(_, surface)| surface).unwrap_or(contact.surface);
+            let splash_pos = Vec3::new(position.x, surface, position.z);
+            spawn_splash(&mut commands, splash_pos, &common_assets, &mut materials);
+            contact.depth = depth;
+        }
+        if let Some((_, surface)) = deepest {
+            contact.surface = surface;
+        }
+        if let Some(mut cc) = cc {
+            if cc.water_depth != depth {
+                cc.water_depth = depth;
+            }
+        }
+    }
+}
+
+/// Bobs floating entities on the surface of deep water, and sinks the rest.
+pub fn apply_buoyancy(
+    mut entities: Query<(Entity, &Buoyancy, &WaterContact, &mut Transform)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, buoyancy, contact, mut transf) in &mut entities {
+        if contact.depth != Some(WaterDepth::Deep) { continue };
+        match buoyancy {
+            Buoyancy::Floats => {
+                let bob = (time.elapsed_seconds() * BOB_SPEED).sin() * BOB_AMPLITUDE;
+                transf.translation.y = contact.surface + bob;
+            },
+            Buoyancy::Sinks => {
+                transf.translation.y -= SINK_SPEED * time.delta_seconds();
+                if transf.translation.y < contact.surface - DEEP_WATER_DEPTH {
+                    commands.entity(entity).despawn_recursive();
+                }
+            },
+        }
+    }
+}
+
+/// Grows and fades splashes, despawning them when they finish.
+pub fn update_splashes(
+    mut splashes: Query<(Entity, &mut Splash, &mut Transform)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut splash, mut transf) in &mut splashes {
+        splash.elapsed += time.delta();
+        if splash.elapsed >= SPLASH_LIFETIME {
+            commands.entity(entity).despawn_recursive();
+            materials.remove(&splash.material);
+            continue;
+        }
+        let t = splash.elapsed.as_secs_f32() / SPLASH_LIFETIME.as_secs_f32();
+        transf.scale = Vec3::splat(SPLASH_SIZE * (1.0 + t * SPLASH_GROWTH));
+        if let Some(material) = materials.get_mut(&splash.material) {
+            material.base_color.set_alpha(SPLASH_ALPHA * (1.0 - t));
+        }
+    }
+}